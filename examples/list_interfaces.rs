@@ -9,8 +9,6 @@
 
 //! List interface example.
 
-use if_addrs;
-
 fn main() {
     let ifaces = if_addrs::get_if_addrs().unwrap();
     println!("Got list of interfaces");
@@ -0,0 +1,208 @@
+// Copyright 2018 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+#[cfg(not(feature = "std"))]
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use core::{fmt, str::FromStr};
+#[cfg(feature = "std")]
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// An address together with the prefix length of its network, such as `192.168.1.5/24`.
+///
+/// The prefix length never exceeds the bit width of the address family.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
+pub struct Cidr {
+    addr: IpAddr,
+    prefix_len: u8,
+}
+
+impl Cidr {
+    /// Create a `Cidr`, or `None` if `prefix_len` is longer than the address.
+    pub fn new(addr: IpAddr, prefix_len: u8) -> Option<Cidr> {
+        if prefix_len > max_prefix_len(addr) {
+            return None;
+        }
+        Some(Cidr { addr, prefix_len })
+    }
+
+    /// Create a `Cidr`, capping `prefix_len` at the length of the address.
+    pub(crate) fn saturating(addr: IpAddr, prefix_len: u32) -> Cidr {
+        Cidr {
+            addr,
+            prefix_len: (prefix_len as u8).min(max_prefix_len(addr)),
+        }
+    }
+
+    /// Create a `Cidr` from an address and its netmask, or `None` if the families differ or the
+    /// netmask is not contiguous.
+    pub fn from_netmask(addr: IpAddr, netmask: IpAddr) -> Option<Cidr> {
+        let prefix_len = match (addr, netmask) {
+            (IpAddr::V4(_), IpAddr::V4(netmask)) => {
+                let netmask = u32::from(netmask);
+                let prefix_len = netmask.leading_ones();
+                if netmask.checked_shl(prefix_len).unwrap_or(0) != 0 {
+                    return None;
+                }
+                prefix_len
+            }
+            (IpAddr::V6(_), IpAddr::V6(netmask)) => {
+                let netmask = u128::from(netmask);
+                let prefix_len = netmask.leading_ones();
+                if netmask.checked_shl(prefix_len).unwrap_or(0) != 0 {
+                    return None;
+                }
+                prefix_len
+            }
+            _ => return None,
+        };
+        Cidr::new(addr, prefix_len as u8)
+    }
+
+    /// The address, which may have host bits set.
+    pub fn addr(&self) -> IpAddr {
+        self.addr
+    }
+
+    /// The prefix length.
+    pub fn prefix_len(&self) -> u8 {
+        self.prefix_len
+    }
+
+    /// The netmask matching the prefix length.
+    pub fn netmask(&self) -> IpAddr {
+        match self.addr {
+            IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::from(v4_mask(self.prefix_len))),
+            IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::from(v6_mask(self.prefix_len))),
+        }
+    }
+
+    /// The network this address belongs to, with the host bits cleared.
+    pub fn network(&self) -> Cidr {
+        let addr = match self.addr {
+            IpAddr::V4(ip) => IpAddr::V4(Ipv4Addr::from(u32::from(ip) & v4_mask(self.prefix_len))),
+            IpAddr::V6(ip) => IpAddr::V6(Ipv6Addr::from(u128::from(ip) & v6_mask(self.prefix_len))),
+        };
+        Cidr {
+            addr,
+            prefix_len: self.prefix_len,
+        }
+    }
+
+    /// The broadcast address of an IPv4 network, with all host bits set. `None` for IPv6,
+    /// which has no broadcast.
+    pub fn broadcast(&self) -> Option<Ipv4Addr> {
+        match self.addr {
+            IpAddr::V4(ip) => Some(Ipv4Addr::from(u32::from(ip) | !v4_mask(self.prefix_len))),
+            IpAddr::V6(_) => None,
+        }
+    }
+
+    /// Check whether `ip` lies in this network. Addresses of the other family never do.
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.addr, ip) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = v4_mask(self.prefix_len);
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = v6_mask(self.prefix_len);
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Cidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix_len)
+    }
+}
+
+impl FromStr for Cidr {
+    type Err = ParseCidrError;
+
+    /// Parse `addr/prefix_len`. A bare address gets the full prefix length of its family.
+    fn from_str(s: &str) -> Result<Cidr, ParseCidrError> {
+        let (addr, prefix_len) = match s.find('/') {
+            Some(pos) => (&s[..pos], Some(&s[pos + 1..])),
+            None => (s, None),
+        };
+        let addr: IpAddr = addr.parse().map_err(|_| ParseCidrError(()))?;
+        let prefix_len = match prefix_len {
+            Some(prefix_len) => prefix_len.parse().map_err(|_| ParseCidrError(()))?,
+            None => max_prefix_len(addr),
+        };
+        Cidr::new(addr, prefix_len).ok_or(ParseCidrError(()))
+    }
+}
+
+/// The error returned when parsing a [`Cidr`] fails.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ParseCidrError(());
+
+impl fmt::Display for ParseCidrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid CIDR notation")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseCidrError {}
+
+fn max_prefix_len(addr: IpAddr) -> u8 {
+    match addr {
+        IpAddr::V4(_) => 32,
+        IpAddr::V6(_) => 128,
+    }
+}
+
+fn v4_mask(prefix_len: u8) -> u32 {
+    u32::MAX
+        .checked_shl(32 - u32::from(prefix_len))
+        .unwrap_or(0)
+}
+
+fn v6_mask(prefix_len: u8) -> u128 {
+    u128::MAX
+        .checked_shl(128 - u32::from(prefix_len))
+        .unwrap_or(0)
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::Cidr;
+    use std::net::{IpAddr, Ipv4Addr};
+
+    #[test]
+    fn test_cidr() {
+        let cidr: Cidr = "192.168.1.5/24".parse().unwrap();
+        assert_eq!(cidr.to_string(), "192.168.1.5/24");
+        assert_eq!(cidr.network().to_string(), "192.168.1.0/24");
+        assert_eq!(cidr.netmask(), IpAddr::V4(Ipv4Addr::new(255, 255, 255, 0)));
+        assert_eq!(cidr.broadcast(), Some(Ipv4Addr::new(192, 168, 1, 255)));
+        assert!(cidr.contains(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 200))));
+        assert!(!cidr.contains(IpAddr::V4(Ipv4Addr::new(192, 168, 2, 1))));
+        assert!(!cidr.contains("fe80::1".parse().unwrap()));
+
+        assert_eq!("fd00::1".parse::<Cidr>().unwrap().prefix_len(), 128);
+        assert_eq!(
+            "fd00::1/64".parse::<Cidr>().unwrap().network().to_string(),
+            "fd00::/64"
+        );
+        assert!("10.0.0.1/33".parse::<Cidr>().is_err());
+        assert!("10.0.0.1/".parse::<Cidr>().is_err());
+
+        let netmask = IpAddr::V4(Ipv4Addr::new(255, 0, 255, 0));
+        assert_eq!(
+            Cidr::from_netmask(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)), netmask),
+            None
+        );
+    }
+}
@@ -0,0 +1,498 @@
+// Copyright 2018 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Changes to the interface configuration of this host, over rtnetlink on Linux and the IP
+//! Helper API on Windows.
+//!
+//! Everything here needs administrative privileges, i.e. `CAP_NET_ADMIN` on Linux.
+
+use crate::IfAddr;
+use std::io;
+
+/// Bring the interface `name` administratively up or down, like `ip link set dev <name> up`.
+///
+/// On Windows `name` is the adapter name that [`get_if_addrs`](crate::get_if_addrs) reports.
+pub fn set_admin_state(name: &str, up: bool) -> io::Result<()> {
+    os::set_admin_state(name, up)
+}
+
+/// Add `addr` with its netmask to the interface `name`, like `ip addr add`. The broadcast
+/// address of an IPv4 address is set too if given; it is ignored on Windows.
+pub fn add_address(name: &str, addr: &IfAddr) -> io::Result<()> {
+    os::add_address(name, addr)
+}
+
+/// Remove `addr` from the interface `name`, like `ip addr del`. Only the IP address and netmask
+/// of `addr` need to match.
+pub fn remove_address(name: &str, addr: &IfAddr) -> io::Result<()> {
+    os::remove_address(name, addr)
+}
+
+/// Set the MTU of the interface `name`, like `ip link set dev <name> mtu <mtu>`. On Windows
+/// this sets the MTU of both IP families.
+pub fn set_mtu(name: &str, mtu: u32) -> io::Result<()> {
+    os::set_mtu(name, mtu)
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) mod os {
+    use crate::nlmsg::{self, push_attr, Socket};
+    use crate::routes::Route;
+    use crate::IfAddr;
+    use std::ffi::CString;
+    use std::io;
+    use std::net::IpAddr;
+
+    const NLM_F_ACK: u16 = 0x4;
+    const NLM_F_EXCL: u16 = 0x200;
+    const NLM_F_CREATE: u16 = 0x400;
+    const RTM_NEWLINK: u16 = 16;
+    const RTM_NEWADDR: u16 = 20;
+    const RTM_DELADDR: u16 = 21;
+    const RTM_NEWROUTE: u16 = 24;
+    const RTM_DELROUTE: u16 = 25;
+    const RT_TABLE_MAIN: u8 = 254;
+    const RTPROT_BOOT: u8 = 3;
+    const RT_SCOPE_UNIVERSE: u8 = 0;
+    const RT_SCOPE_LINK: u8 = 253;
+    const RT_SCOPE_NOWHERE: u8 = 255;
+    const RTN_UNICAST: u8 = 1;
+    const RTA_DST: u16 = 1;
+    const RTA_OIF: u16 = 4;
+    const RTA_GATEWAY: u16 = 5;
+    const IFLA_MTU: u16 = 4;
+    const IFA_ADDRESS: u16 = 1;
+    const IFA_LOCAL: u16 = 2;
+    const IFA_BROADCAST: u16 = 4;
+    const IFF_UP: u32 = 0x1;
+
+    pub fn set_admin_state(name: &str, up: bool) -> io::Result<()> {
+        let index = if_nametoindex(name)?;
+        // Change only IFF_UP.
+        let flags = if up { IFF_UP } else { 0 };
+        request(RTM_NEWLINK, 0, &ifinfomsg(index, flags, IFF_UP))
+    }
+
+    pub fn set_mtu(name: &str, mtu: u32) -> io::Result<()> {
+        let index = if_nametoindex(name)?;
+        request(RTM_NEWLINK, 0, &mtu_msg(index, mtu))
+    }
+
+    /// Build a `struct ifinfomsg` for the interface `index`, setting the flags in `change` to
+    /// those in `flags`.
+    fn ifinfomsg(index: u32, flags: u32, change: u32) -> Vec<u8> {
+        let mut msg = Vec::with_capacity(16);
+        msg.extend_from_slice(&[libc::AF_UNSPEC as u8, 0, 0, 0]);
+        msg.extend_from_slice(&index.to_ne_bytes());
+        msg.extend_from_slice(&flags.to_ne_bytes());
+        msg.extend_from_slice(&change.to_ne_bytes());
+        msg
+    }
+
+    /// Build a `struct ifinfomsg` for the interface `index` with an `IFLA_MTU` of `mtu`.
+    fn mtu_msg(index: u32, mtu: u32) -> Vec<u8> {
+        let mut msg = ifinfomsg(index, 0, 0);
+        push_attr(&mut msg, IFLA_MTU, &mtu.to_ne_bytes());
+        msg
+    }
+
+    pub fn add_address(name: &str, addr: &IfAddr) -> io::Result<()> {
+        let index = if_nametoindex(name)?;
+        request(
+            RTM_NEWADDR,
+            NLM_F_CREATE | NLM_F_EXCL,
+            &ifaddrmsg(index, addr, true),
+        )
+    }
+
+    pub fn remove_address(name: &str, addr: &IfAddr) -> io::Result<()> {
+        let index = if_nametoindex(name)?;
+        request(RTM_DELADDR, 0, &ifaddrmsg(index, addr, false))
+    }
+
+    /// Build a `struct ifaddrmsg` for `addr` on the interface `index`, followed by its
+    /// attributes.
+    fn ifaddrmsg(index: u32, addr: &IfAddr, with_broadcast: bool) -> Vec<u8> {
+        let family = match addr {
+            IfAddr::V4(_) => libc::AF_INET,
+            IfAddr::V6(_) => libc::AF_INET6,
+        };
+        let mut msg = vec![family as u8, addr.cidr().prefix_len(), 0, 0];
+        msg.extend_from_slice(&index.to_ne_bytes());
+        let ip = ip_octets(addr.ip());
+        push_attr(&mut msg, IFA_LOCAL, &ip);
+        push_attr(&mut msg, IFA_ADDRESS, &ip);
+        if let IfAddr::V4(ifv4_addr) = addr {
+            if let Some(broadcast) = ifv4_addr.broadcast.filter(|_| with_broadcast) {
+                push_attr(&mut msg, IFA_BROADCAST, &broadcast.octets());
+            }
+        }
+        msg
+    }
+
+    pub fn add_route(route: &Route) -> io::Result<()> {
+        request(RTM_NEWROUTE, NLM_F_CREATE | NLM_F_EXCL, &rtmsg(route, true))
+    }
+
+    pub fn delete_route(route: &Route) -> io::Result<()> {
+        request(RTM_DELROUTE, 0, &rtmsg(route, false))
+    }
+
+    /// Build a `struct rtmsg` for `route` in the main table, followed by its attributes.
+    fn rtmsg(route: &Route, add: bool) -> Vec<u8> {
+        let family = match route.destination {
+            IpAddr::V4(_) => libc::AF_INET,
+            IpAddr::V6(_) => libc::AF_INET6,
+        };
+        let scope = match (add, route.gateway) {
+            // Deleting matches a route of any scope.
+            (false, _) => RT_SCOPE_NOWHERE,
+            (true, Some(_)) => RT_SCOPE_UNIVERSE,
+            (true, None) => RT_SCOPE_LINK,
+        };
+        let protocol = if add { RTPROT_BOOT } else { 0 };
+        let mut msg = vec![
+            family as u8,
+            route.cidr().prefix_len(),
+            0,
+            0,
+            RT_TABLE_MAIN,
+            protocol,
+            scope,
+            RTN_UNICAST,
+        ];
+        msg.extend_from_slice(&0u32.to_ne_bytes());
+        if !route.is_default() {
+            // The kernel refuses a destination with host bits set.
+            push_attr(&mut msg, RTA_DST, &ip_octets(route.cidr().network().addr()));
+        }
+        if let Some(gateway) = route.gateway {
+            push_attr(&mut msg, RTA_GATEWAY, &ip_octets(gateway));
+        }
+        if route.index != 0 {
+            push_attr(&mut msg, RTA_OIF, &route.index.to_ne_bytes());
+        }
+        msg
+    }
+
+    fn ip_octets(ip: IpAddr) -> Vec<u8> {
+        match ip {
+            IpAddr::V4(ip) => ip.octets().to_vec(),
+            IpAddr::V6(ip) => ip.octets().to_vec(),
+        }
+    }
+
+    /// Send an rtnetlink request with `body` after the `nlmsghdr` and wait for the kernel to
+    /// acknowledge it.
+    pub fn request(msg_type: u16, flags: u16, body: &[u8]) -> io::Result<()> {
+        let socket = Socket::open(libc::NETLINK_ROUTE)?;
+        socket.transact(&nlmsg::request(msg_type, NLM_F_ACK | flags, 1, body))?;
+        Ok(())
+    }
+
+    #[allow(unsafe_code)]
+    pub fn if_nametoindex(name: &str) -> io::Result<u32> {
+        let name =
+            CString::new(name).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+        match unsafe { libc::if_nametoindex(name.as_ptr()) } {
+            0 => Err(io::Error::last_os_error()),
+            index => Ok(index),
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::{ifaddrmsg, mtu_msg, rtmsg, IFA_ADDRESS, IFA_BROADCAST, IFA_LOCAL, IFLA_MTU};
+        use super::{RTA_DST, RTA_GATEWAY, RTA_OIF, RT_SCOPE_NOWHERE, RT_SCOPE_UNIVERSE};
+        use crate::nlmsg::attrs;
+        use crate::routes::Route;
+        use crate::{IfAddr, Ifv4Addr, Ifv6Addr};
+        use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+        #[test]
+        fn test_mtu_msg() {
+            let msg = mtu_msg(7, 1400);
+            assert_eq!(msg[..4], [libc::AF_UNSPEC as u8, 0, 0, 0]);
+            assert_eq!(msg[4..8], 7u32.to_ne_bytes());
+            // No flags are changed.
+            assert_eq!(msg[8..16], [0; 8]);
+            assert_eq!(
+                attrs(&msg[16..]).collect::<Vec<_>>(),
+                vec![(IFLA_MTU, &1400u32.to_ne_bytes()[..])]
+            );
+        }
+
+        #[test]
+        fn test_ifaddrmsg() {
+            let addr = IfAddr::V4(Ifv4Addr::new(
+                Ipv4Addr::new(192, 0, 2, 1),
+                Ipv4Addr::new(255, 255, 255, 0),
+                Some(Ipv4Addr::new(192, 0, 2, 255)),
+            ));
+            let msg = ifaddrmsg(7, &addr, true);
+            assert_eq!(msg[..4], [libc::AF_INET as u8, 24, 0, 0]);
+            assert_eq!(msg[4..8], 7u32.to_ne_bytes());
+            let ip = [192, 0, 2, 1];
+            assert_eq!(
+                attrs(&msg[8..]).collect::<Vec<_>>(),
+                vec![
+                    (IFA_LOCAL, &ip[..]),
+                    (IFA_ADDRESS, &ip[..]),
+                    (IFA_BROADCAST, &[192, 0, 2, 255][..]),
+                ]
+            );
+            // Removing matches on the address alone.
+            let msg = ifaddrmsg(7, &addr, false);
+            assert_eq!(attrs(&msg[8..]).count(), 2);
+
+            let ip: Ipv6Addr = "2001:db8::1".parse().unwrap();
+            let addr = IfAddr::V6(Ifv6Addr::new(ip, Ipv6Addr::from(!0u128 << 64), None));
+            let msg = ifaddrmsg(7, &addr, true);
+            assert_eq!(msg[..4], [libc::AF_INET6 as u8, 64, 0, 0]);
+            assert_eq!(
+                attrs(&msg[8..]).collect::<Vec<_>>(),
+                vec![
+                    (IFA_LOCAL, &ip.octets()[..]),
+                    (IFA_ADDRESS, &ip.octets()[..])
+                ]
+            );
+        }
+
+        #[test]
+        fn test_rtmsg() {
+            let route = Route {
+                destination: IpAddr::V4(Ipv4Addr::new(10, 0, 0, 5)),
+                prefix_len: 24,
+                gateway: Some(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1))),
+                index: 3,
+            };
+            let msg = rtmsg(&route, true);
+            assert_eq!(
+                msg[..8],
+                [libc::AF_INET as u8, 24, 0, 0, 254, 3, RT_SCOPE_UNIVERSE, 1]
+            );
+            // The destination is masked to the prefix.
+            assert_eq!(
+                attrs(&msg[12..]).collect::<Vec<_>>(),
+                vec![
+                    (RTA_DST, &[10, 0, 0, 0][..]),
+                    (RTA_GATEWAY, &[192, 0, 2, 1][..]),
+                    (RTA_OIF, &3u32.to_ne_bytes()[..]),
+                ]
+            );
+
+            let gateway: Ipv6Addr = "fe80::1".parse().unwrap();
+            let default = Route {
+                destination: IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+                prefix_len: 0,
+                gateway: Some(IpAddr::V6(gateway)),
+                index: 0,
+            };
+            let msg = rtmsg(&default, false);
+            assert_eq!(
+                msg[..8],
+                [libc::AF_INET6 as u8, 0, 0, 0, 254, 0, RT_SCOPE_NOWHERE, 1]
+            );
+            assert_eq!(
+                attrs(&msg[12..]).collect::<Vec<_>>(),
+                vec![(RTA_GATEWAY, &gateway.octets()[..])]
+            );
+        }
+    }
+}
+
+#[cfg(windows)]
+pub(crate) mod os {
+    use crate::routes::Route;
+    use crate::IfAddr;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+    use std::{io, mem};
+    use winapi::shared::ifmib::MIB_IFROW;
+    use winapi::shared::ipifcons::{MIB_IF_ADMIN_STATUS_DOWN, MIB_IF_ADMIN_STATUS_UP};
+    use winapi::shared::netioapi::{
+        CreateIpForwardEntry2, CreateUnicastIpAddressEntry, DeleteIpForwardEntry2,
+        DeleteUnicastIpAddressEntry, GetIpInterfaceEntry, InitializeIpForwardEntry,
+        InitializeIpInterfaceEntry, InitializeUnicastIpAddressEntry, SetIpInterfaceEntry,
+        MIB_IPFORWARD_ROW2, MIB_IPINTERFACE_ROW, MIB_UNICASTIPADDRESS_ROW,
+    };
+    use winapi::shared::winerror::{ERROR_NOT_FOUND, NO_ERROR};
+    use winapi::shared::ws2def::{AF_INET, AF_INET6};
+    use winapi::shared::ws2ipdef::SOCKADDR_INET;
+    use winapi::um::iphlpapi::SetIfEntry;
+
+    #[allow(unsafe_code)]
+    pub fn set_admin_state(name: &str, up: bool) -> io::Result<()> {
+        let mut row: MIB_IFROW = unsafe { mem::zeroed() };
+        row.dwIndex = adapter_index(name)?;
+        row.dwAdminStatus = if up {
+            MIB_IF_ADMIN_STATUS_UP
+        } else {
+            MIB_IF_ADMIN_STATUS_DOWN
+        };
+        check(unsafe { SetIfEntry(&mut row) })
+    }
+
+    /// Set the MTU of every IP family enabled on the adapter.
+    #[allow(unsafe_code)]
+    pub fn set_mtu(name: &str, mtu: u32) -> io::Result<()> {
+        let index = adapter_index(name)?;
+        let mut found = false;
+        for &family in &[AF_INET, AF_INET6] {
+            let mut row: MIB_IPINTERFACE_ROW = unsafe { mem::zeroed() };
+            unsafe { InitializeIpInterfaceEntry(&mut row) };
+            row.Family = family as u16;
+            row.InterfaceIndex = index;
+            match unsafe { GetIpInterfaceEntry(&mut row) } {
+                NO_ERROR => found = true,
+                // The family is disabled on this adapter.
+                ERROR_NOT_FOUND => continue,
+                err => return check(err),
+            }
+            row.NlMtu = mtu;
+            // SetIpInterfaceEntry rejects IPv4 rows with a site prefix length.
+            row.SitePrefixLength = 0;
+            check(unsafe { SetIpInterfaceEntry(&mut row) })?;
+        }
+        if found {
+            Ok(())
+        } else {
+            check(ERROR_NOT_FOUND)
+        }
+    }
+
+    #[allow(unsafe_code)]
+    pub fn add_address(name: &str, addr: &IfAddr) -> io::Result<()> {
+        let row = unicast_row(name, addr)?;
+        check(unsafe { CreateUnicastIpAddressEntry(&row) })
+    }
+
+    #[allow(unsafe_code)]
+    pub fn remove_address(name: &str, addr: &IfAddr) -> io::Result<()> {
+        let row = unicast_row(name, addr)?;
+        check(unsafe { DeleteUnicastIpAddressEntry(&row) })
+    }
+
+    #[allow(unsafe_code)]
+    fn unicast_row(name: &str, addr: &IfAddr) -> io::Result<MIB_UNICASTIPADDRESS_ROW> {
+        let mut row: MIB_UNICASTIPADDRESS_ROW = unsafe { mem::zeroed() };
+        unsafe { InitializeUnicastIpAddressEntry(&mut row) };
+        row.Address = to_sockaddr_inet(addr.ip());
+        row.InterfaceIndex = adapter_index(name)?;
+        row.OnLinkPrefixLength = addr.cidr().prefix_len();
+        Ok(row)
+    }
+
+    #[allow(unsafe_code)]
+    pub fn add_route(route: &Route) -> io::Result<()> {
+        check(unsafe { CreateIpForwardEntry2(&forward_row(route)) })
+    }
+
+    #[allow(unsafe_code)]
+    pub fn delete_route(route: &Route) -> io::Result<()> {
+        check(unsafe { DeleteIpForwardEntry2(&forward_row(route)) })
+    }
+
+    #[allow(unsafe_code)]
+    fn forward_row(route: &Route) -> MIB_IPFORWARD_ROW2 {
+        let mut row: MIB_IPFORWARD_ROW2 = unsafe { mem::zeroed() };
+        unsafe { InitializeIpForwardEntry(&mut row) };
+        row.InterfaceIndex = route.index;
+        row.DestinationPrefix.Prefix = to_sockaddr_inet(route.cidr().network().addr());
+        row.DestinationPrefix.PrefixLength = route.cidr().prefix_len();
+        // An on-link route has the unspecified address of its family as the next hop.
+        let unspecified = match route.destination {
+            IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+        };
+        row.NextHop = to_sockaddr_inet(route.gateway.unwrap_or(unspecified));
+        row
+    }
+
+    #[allow(unsafe_code)]
+    fn to_sockaddr_inet(ip: IpAddr) -> SOCKADDR_INET {
+        let mut sa: SOCKADDR_INET = unsafe { mem::zeroed() };
+        unsafe {
+            match ip {
+                IpAddr::V4(ip) => {
+                    let sin = sa.Ipv4_mut();
+                    sin.sin_family = AF_INET as u16;
+                    *sin.sin_addr.S_un.S_addr_mut() = u32::from(ip).to_be();
+                }
+                IpAddr::V6(ip) => {
+                    let sin6 = sa.Ipv6_mut();
+                    sin6.sin6_family = AF_INET6 as u16;
+                    *sin6.sin6_addr.u.Byte_mut() = ip.octets();
+                }
+            }
+        }
+        sa
+    }
+
+    fn check(ret: u32) -> io::Result<()> {
+        match ret {
+            NO_ERROR => Ok(()),
+            err => Err(io::Error::from_raw_os_error(err as i32)),
+        }
+    }
+
+    /// Look up the interface index of the adapter `name`.
+    pub fn adapter_index(name: &str) -> io::Result<u32> {
+        crate::windows::adapters_only()?
+            .iter()
+            .find(|ifaddr| ifaddr.name.eq_ignore_ascii_case(name))
+            .map(|ifaddr| match ifaddr.if_index {
+                0 => ifaddr.ipv6_if_index,
+                index => index,
+            })
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("no adapter named {}", name),
+                )
+            })
+    }
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+pub(crate) mod os {
+    use crate::routes::Route;
+    use crate::IfAddr;
+    use std::io;
+
+    pub fn set_admin_state(_name: &str, _up: bool) -> io::Result<()> {
+        Err(unsupported())
+    }
+
+    pub fn set_mtu(_name: &str, _mtu: u32) -> io::Result<()> {
+        Err(unsupported())
+    }
+
+    pub fn add_route(_route: &Route) -> io::Result<()> {
+        Err(unsupported())
+    }
+
+    pub fn delete_route(_route: &Route) -> io::Result<()> {
+        Err(unsupported())
+    }
+
+    pub fn add_address(_name: &str, _addr: &IfAddr) -> io::Result<()> {
+        Err(unsupported())
+    }
+
+    pub fn remove_address(_name: &str, _addr: &IfAddr) -> io::Result<()> {
+        Err(unsupported())
+    }
+
+    fn unsupported() -> io::Error {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            "interface configuration is not supported on this platform",
+        )
+    }
+}
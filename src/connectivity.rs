@@ -0,0 +1,154 @@
+// Copyright 2018 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Network connectivity hints reported by Windows.
+//!
+//! These tell an interface that is merely up apart from one that actually reaches the internet.
+//! The underlying `GetNetworkConnectivityHint` API needs Windows 10 version 2004 or later, and is
+//! looked up at runtime so older versions get an error instead of failing to load.
+
+use std::io;
+use std::mem;
+use winapi::shared::minwindef::FARPROC;
+use winapi::um::libloaderapi::{GetProcAddress, LoadLibraryA};
+
+/// How far the network is reachable.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum ConnectivityLevel {
+    /// Windows could not determine the level.
+    Unknown,
+    /// No network connectivity.
+    None,
+    /// Only the local network is reachable.
+    LocalAccess,
+    /// The internet is reachable.
+    InternetAccess,
+    /// The internet is reachable, but only through a captive portal or similar constraint.
+    ConstrainedInternetAccess,
+    /// The connectivity of this interface is hidden from the caller.
+    Hidden,
+}
+
+/// How the network is billed.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum ConnectivityCost {
+    /// Windows could not determine the cost.
+    Unknown,
+    /// Unlimited use at no extra charge.
+    Unrestricted,
+    /// Use is capped by a data plan.
+    Fixed,
+    /// Use is charged by volume.
+    Variable,
+}
+
+/// Connectivity as reported by `GetNetworkConnectivityHint`.
+///
+/// Windows reports a single hint per host or per interface; it does not distinguish IPv4 from
+/// IPv6, so both families of an interface share one hint.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct ConnectivityHint {
+    /// How far the network is reachable.
+    pub level: ConnectivityLevel,
+    /// How the network is billed.
+    pub cost: ConnectivityCost,
+    /// Whether the data plan is close to its limit.
+    pub approaching_data_limit: bool,
+    /// Whether the data plan is over its limit.
+    pub over_data_limit: bool,
+    /// Whether the connection is roaming.
+    pub roaming: bool,
+}
+
+/// Get the connectivity hint of the host as a whole.
+#[allow(unsafe_code)]
+pub fn connectivity_hint() -> io::Result<ConnectivityHint> {
+    let func: GetNetworkConnectivityHintFn =
+        unsafe { mem::transmute(lookup(b"GetNetworkConnectivityHint\0")?) };
+    let mut hint = NlNetworkConnectivityHint::default();
+    check(unsafe { func(&mut hint) })?;
+    Ok(hint.into())
+}
+
+/// Get the connectivity hint of the interface with the given index.
+#[allow(unsafe_code)]
+pub fn connectivity_hint_for_interface(index: u32) -> io::Result<ConnectivityHint> {
+    let func: GetNetworkConnectivityHintForInterfaceFn =
+        unsafe { mem::transmute(lookup(b"GetNetworkConnectivityHintForInterface\0")?) };
+    let mut hint = NlNetworkConnectivityHint::default();
+    check(unsafe { func(index, &mut hint) })?;
+    Ok(hint.into())
+}
+
+/// `NL_NETWORK_CONNECTIVITY_HINT`
+#[repr(C)]
+#[derive(Default)]
+struct NlNetworkConnectivityHint {
+    connectivity_level: i32,
+    connectivity_cost: i32,
+    approaching_data_limit: u8,
+    over_data_limit: u8,
+    roaming: u8,
+}
+
+impl From<NlNetworkConnectivityHint> for ConnectivityHint {
+    fn from(hint: NlNetworkConnectivityHint) -> Self {
+        ConnectivityHint {
+            level: match hint.connectivity_level {
+                1 => ConnectivityLevel::None,
+                2 => ConnectivityLevel::LocalAccess,
+                3 => ConnectivityLevel::InternetAccess,
+                4 => ConnectivityLevel::ConstrainedInternetAccess,
+                5 => ConnectivityLevel::Hidden,
+                _ => ConnectivityLevel::Unknown,
+            },
+            cost: match hint.connectivity_cost {
+                1 => ConnectivityCost::Unrestricted,
+                2 => ConnectivityCost::Fixed,
+                3 => ConnectivityCost::Variable,
+                _ => ConnectivityCost::Unknown,
+            },
+            approaching_data_limit: hint.approaching_data_limit != 0,
+            over_data_limit: hint.over_data_limit != 0,
+            roaming: hint.roaming != 0,
+        }
+    }
+}
+
+type GetNetworkConnectivityHintFn =
+    unsafe extern "system" fn(hint: *mut NlNetworkConnectivityHint) -> i32;
+type GetNetworkConnectivityHintForInterfaceFn =
+    unsafe extern "system" fn(index: u32, hint: *mut NlNetworkConnectivityHint) -> i32;
+
+/// Look up a NUL-terminated function name in iphlpapi.dll.
+#[allow(unsafe_code)]
+fn lookup(name: &[u8]) -> io::Result<FARPROC> {
+    let module = unsafe { LoadLibraryA(b"iphlpapi.dll\0".as_ptr() as *const _) };
+    if module.is_null() {
+        return Err(io::Error::last_os_error());
+    }
+    let func = unsafe { GetProcAddress(module, name.as_ptr() as *const _) };
+    if func.is_null() {
+        return Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "network connectivity hints need Windows 10 version 2004 or later",
+        ));
+    }
+    Ok(func)
+}
+
+fn check(status: i32) -> io::Result<()> {
+    match status {
+        0 => Ok(()),
+        status => Err(io::Error::other(format!(
+            "GetNetworkConnectivityHint failed with status {:#x}",
+            status
+        ))),
+    }
+}
@@ -0,0 +1,250 @@
+// Copyright 2018 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Driver and bus details of interfaces, from ethtool on Linux and the network adapter class in
+//! the registry on Windows.
+
+use crate::Interface;
+use std::io;
+
+/// The driver behind an interface and where its hardware sits.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct DriverInfo {
+    /// The name of the driver, such as `e1000e` on Linux or the driver description on Windows.
+    pub driver: String,
+    /// The version of the driver, if reported.
+    pub version: Option<String>,
+    /// The firmware version of the device, if reported. Linux only.
+    pub firmware_version: Option<String>,
+    /// The location of the device, such as the PCI address `0000:00:1f.6` on Linux or the
+    /// device instance ID on Windows, if the device sits on a bus.
+    pub bus_info: Option<String>,
+}
+
+/// Get the driver details of `iface`.
+///
+/// Returns `None` if the interface has no driver details, e.g. the Linux loopback device.
+pub fn driver_info(iface: &Interface) -> io::Result<Option<DriverInfo>> {
+    os::driver_info(iface)
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod os {
+    use super::DriverInfo;
+    use crate::Interface;
+    use libc::c_void;
+    use std::io;
+    use std::os::unix::io::{AsRawFd, FromRawFd, OwnedFd};
+
+    const SIOCETHTOOL: u32 = 0x8946;
+    const ETHTOOL_GDRVINFO: u32 = 0x3;
+
+    /// `struct ethtool_drvinfo` from `linux/ethtool.h`.
+    #[repr(C)]
+    struct EthtoolDrvinfo {
+        cmd: u32,
+        driver: [u8; 32],
+        version: [u8; 32],
+        fw_version: [u8; 32],
+        bus_info: [u8; 32],
+        erom_version: [u8; 32],
+        reserved2: [u8; 12],
+        n_priv_flags: u32,
+        n_stats: u32,
+        testinfo_len: u32,
+        eedump_len: u32,
+        regdump_len: u32,
+    }
+
+    /// `struct ifreq` with the `ifr_data` member of its union, padded to the full size.
+    #[repr(C)]
+    struct IfReq {
+        name: [u8; libc::IFNAMSIZ],
+        data: *mut c_void,
+        padding: [u8; 16],
+    }
+
+    #[allow(unsafe_code)]
+    pub fn driver_info(iface: &Interface) -> io::Result<Option<DriverInfo>> {
+        let mut drvinfo = EthtoolDrvinfo {
+            cmd: ETHTOOL_GDRVINFO,
+            driver: [0; 32],
+            version: [0; 32],
+            fw_version: [0; 32],
+            bus_info: [0; 32],
+            erom_version: [0; 32],
+            reserved2: [0; 12],
+            n_priv_flags: 0,
+            n_stats: 0,
+            testinfo_len: 0,
+            eedump_len: 0,
+            regdump_len: 0,
+        };
+        let mut ifreq = IfReq {
+            name: [0; libc::IFNAMSIZ],
+            data: &mut drvinfo as *mut EthtoolDrvinfo as *mut c_void,
+            padding: [0; 16],
+        };
+        // Alias labels such as `eth0:1` name the link they belong to.
+        let name = crate::link_name(&iface.name);
+        // Leave room for the terminating NUL.
+        if name.len() >= libc::IFNAMSIZ {
+            return Ok(None);
+        }
+        ifreq.name[..name.len()].copy_from_slice(name.as_bytes());
+
+        let fd = unsafe { libc::socket(libc::AF_INET, libc::SOCK_DGRAM | libc::SOCK_CLOEXEC, 0) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let socket = unsafe { OwnedFd::from_raw_fd(fd) };
+        if unsafe { libc::ioctl(socket.as_raw_fd(), SIOCETHTOOL as _, &mut ifreq) } < 0 {
+            let err = io::Error::last_os_error();
+            // No such interface, or a driver without ethtool support.
+            return match err.raw_os_error() {
+                Some(libc::ENODEV) | Some(libc::EOPNOTSUPP) => Ok(None),
+                _ => Err(err),
+            };
+        }
+
+        Ok(padded_string(&drvinfo.driver).map(|driver| DriverInfo {
+            driver,
+            version: padded_string(&drvinfo.version),
+            firmware_version: padded_string(&drvinfo.fw_version),
+            bus_info: padded_string(&drvinfo.bus_info),
+        }))
+    }
+
+    /// Turn the NUL-padded `buf` into a string, `None` if it is empty.
+    fn padded_string(buf: &[u8]) -> Option<String> {
+        let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+        Some(String::from_utf8_lossy(&buf[..len]).into_owned()).filter(|s| !s.is_empty())
+    }
+}
+
+#[cfg(windows)]
+mod os {
+    use super::DriverInfo;
+    use crate::Interface;
+    use std::{io, ptr};
+    use winapi::shared::minwindef::{DWORD, HKEY};
+    use winapi::shared::winerror::{ERROR_NO_MORE_ITEMS, ERROR_SUCCESS};
+    use winapi::um::winnt::KEY_READ;
+    use winapi::um::winreg::{
+        RegCloseKey, RegEnumKeyExW, RegGetValueW, RegOpenKeyExW, HKEY_LOCAL_MACHINE, RRF_RT_REG_SZ,
+    };
+
+    /// The device class of network adapters, with one numbered subkey per adapter.
+    const ADAPTER_CLASS: &str =
+        r"SYSTEM\CurrentControlSet\Control\Class\{4d36e972-e325-11ce-bfc1-08002be10318}";
+
+    struct Key(HKEY);
+
+    impl Drop for Key {
+        #[allow(unsafe_code)]
+        fn drop(&mut self) {
+            unsafe { RegCloseKey(self.0) };
+        }
+    }
+
+    /// Find the adapter subkey whose `NetCfgInstanceId` is the adapter name GetAdaptersAddresses
+    /// reports, and read the driver details from it.
+    #[allow(unsafe_code)]
+    pub fn driver_info(iface: &Interface) -> io::Result<Option<DriverInfo>> {
+        let mut key = ptr::null_mut();
+        let ret = unsafe {
+            RegOpenKeyExW(
+                HKEY_LOCAL_MACHINE,
+                wide(ADAPTER_CLASS).as_ptr(),
+                0,
+                KEY_READ,
+                &mut key,
+            )
+        };
+        if ret as DWORD != ERROR_SUCCESS {
+            return Err(io::Error::from_raw_os_error(ret));
+        }
+        let key = Key(key);
+
+        for index in 0.. {
+            let mut subkey = [0u16; 256];
+            let mut len = subkey.len() as DWORD;
+            let ret = unsafe {
+                RegEnumKeyExW(
+                    key.0,
+                    index,
+                    subkey.as_mut_ptr(),
+                    &mut len,
+                    ptr::null_mut(),
+                    ptr::null_mut(),
+                    ptr::null_mut(),
+                    ptr::null_mut(),
+                )
+            };
+            match ret as DWORD {
+                ERROR_SUCCESS => {}
+                ERROR_NO_MORE_ITEMS => break,
+                _ => return Err(io::Error::from_raw_os_error(ret)),
+            }
+            let subkey = &subkey[..=len as usize];
+            match value(&key, subkey, "NetCfgInstanceId") {
+                Some(id) if id.eq_ignore_ascii_case(&iface.name) => {}
+                _ => continue,
+            }
+            return Ok(value(&key, subkey, "DriverDesc").map(|driver| DriverInfo {
+                driver,
+                version: value(&key, subkey, "DriverVersion"),
+                firmware_version: None,
+                bus_info: value(&key, subkey, "DeviceInstanceID"),
+            }));
+        }
+        Ok(None)
+    }
+
+    /// Read the string value `name` of `subkey`, a NUL-terminated UTF-16 key name.
+    #[allow(unsafe_code)]
+    fn value(key: &Key, subkey: &[u16], name: &str) -> Option<String> {
+        let mut buf = [0u16; 512];
+        let mut size = (buf.len() * 2) as DWORD;
+        let ret = unsafe {
+            RegGetValueW(
+                key.0,
+                subkey.as_ptr(),
+                wide(name).as_ptr(),
+                RRF_RT_REG_SZ,
+                ptr::null_mut(),
+                buf.as_mut_ptr() as *mut _,
+                &mut size,
+            )
+        };
+        if ret as DWORD != ERROR_SUCCESS {
+            return None;
+        }
+        let len = buf.iter().position(|&c| c == 0).unwrap_or(buf.len());
+        Some(String::from_utf16_lossy(&buf[..len])).filter(|s| !s.is_empty())
+    }
+
+    fn wide(s: &str) -> Vec<u16> {
+        s.encode_utf16().chain(Some(0)).collect()
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", windows)))]
+mod os {
+    use super::DriverInfo;
+    use crate::Interface;
+    use std::io;
+
+    pub fn driver_info(_iface: &Interface) -> io::Result<Option<DriverInfo>> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "driver details are not supported on this platform",
+        ))
+    }
+}
@@ -0,0 +1,172 @@
+// Copyright 2018 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::{IfAddr, Ifv4Addr, Ifv6Addr, Interface};
+use serde_json::Value;
+use std::convert::TryFrom;
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// Environment variable naming a JSON file whose interfaces replace or augment the enumeration
+/// results of [`get_if_addrs`](crate::get_if_addrs).
+///
+/// The file holds an object with an `interfaces` array and an optional `mode`, either
+/// `"replace"` (the default) or `"augment"`:
+///
+/// ```json
+/// {
+///     "mode": "augment",
+///     "interfaces": [
+///         { "name": "eth0", "ip": "10.0.0.5", "netmask": "255.255.255.0", "broadcast": "10.0.0.255" },
+///         { "name": "eth0", "ip": "2001:db8::5", "netmask": "ffff:ffff:ffff:ffff::" }
+///     ]
+/// }
+/// ```
+///
+/// `netmask`, `broadcast` and the numeric `index` may be omitted.
+pub const OVERRIDE_ENV_VAR: &str = "IF_ADDRS_OVERRIDE";
+
+pub(crate) struct Override {
+    pub augment: bool,
+    pub interfaces: Vec<Interface>,
+}
+
+pub(crate) fn from_env() -> io::Result<Option<Override>> {
+    match std::env::var_os(OVERRIDE_ENV_VAR) {
+        Some(path) => load(path).map(Some),
+        None => Ok(None),
+    }
+}
+
+fn load(path: OsString) -> io::Result<Override> {
+    let contents = fs::read_to_string(path)?;
+    parse(&contents).map_err(|msg| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid {}: {}", OVERRIDE_ENV_VAR, msg),
+        )
+    })
+}
+
+fn parse(contents: &str) -> Result<Override, String> {
+    let root: Value = serde_json::from_str(contents).map_err(|e| e.to_string())?;
+    let augment = match root.get("mode").map(Value::as_str) {
+        None | Some(Some("replace")) => false,
+        Some(Some("augment")) => true,
+        Some(_) => return Err("`mode` must be \"replace\" or \"augment\"".to_string()),
+    };
+    let entries = root
+        .get("interfaces")
+        .and_then(Value::as_array)
+        .ok_or_else(|| "missing `interfaces` array".to_string())?;
+
+    let mut interfaces = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let name =
+            string_field(entry, "name")?.ok_or_else(|| "interface without `name`".to_string())?;
+        let ip = ip_field(entry, "ip")?.ok_or_else(|| format!("`{}` without `ip`", name))?;
+        let netmask = ip_field(entry, "netmask")?;
+        let index = match entry.get("index") {
+            None | Some(Value::Null) => None,
+            Some(index) => Some(
+                index
+                    .as_u64()
+                    .and_then(|index| u32::try_from(index).ok())
+                    .ok_or_else(|| "`index` must be a 32-bit unsigned integer".to_string())?,
+            ),
+        };
+        let broadcast = ip_field(entry, "broadcast")?;
+
+        let addr = match (ip, netmask, broadcast) {
+            (IpAddr::V4(ip), None | Some(IpAddr::V4(_)), None | Some(IpAddr::V4(_))) => {
+                IfAddr::V4(Ifv4Addr {
+                    ip,
+                    netmask: match netmask {
+                        Some(IpAddr::V4(netmask)) => netmask,
+                        _ => Ipv4Addr::new(0, 0, 0, 0),
+                    },
+                    broadcast: match broadcast {
+                        Some(IpAddr::V4(broadcast)) => Some(broadcast),
+                        _ => None,
+                    },
+                })
+            }
+            (IpAddr::V6(ip), None | Some(IpAddr::V6(_)), None | Some(IpAddr::V6(_))) => {
+                IfAddr::V6(Ifv6Addr {
+                    ip,
+                    netmask: match netmask {
+                        Some(IpAddr::V6(netmask)) => netmask,
+                        _ => Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 0),
+                    },
+                    broadcast: match broadcast {
+                        Some(IpAddr::V6(broadcast)) => Some(broadcast),
+                        _ => None,
+                    },
+                })
+            }
+            _ => return Err(format!("`{}` mixes address families", name)),
+        };
+        interfaces.push(Interface::new(name, addr, index));
+    }
+
+    Ok(Override {
+        augment,
+        interfaces,
+    })
+}
+
+fn string_field(entry: &Value, key: &str) -> Result<Option<String>, String> {
+    match entry.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(s)) => Ok(Some(s.clone())),
+        Some(_) => Err(format!("`{}` must be a string", key)),
+    }
+}
+
+fn ip_field(entry: &Value, key: &str) -> Result<Option<IpAddr>, String> {
+    match string_field(entry, key)? {
+        Some(s) => s
+            .parse()
+            .map(Some)
+            .map_err(|_| format!("invalid address `{}`", s)),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+    use std::net::{IpAddr, Ipv4Addr};
+
+    #[test]
+    fn test_parse() {
+        let parsed = parse(
+            r#"{"mode": "augment", "interfaces": [
+                {"name": "eth0", "ip": "10.0.0.5", "netmask": "255.255.255.0"},
+                {"name": "eth0", "ip": "2001:db8::5"}
+            ]}"#,
+        )
+        .unwrap();
+        assert!(parsed.augment);
+        assert_eq!(2, parsed.interfaces.len());
+        assert_eq!(
+            IpAddr::V4(Ipv4Addr::new(10, 0, 0, 5)),
+            parsed.interfaces[0].ip()
+        );
+
+        assert!(!parse(r#"{"interfaces": []}"#).unwrap().augment);
+        assert!(parse(
+            r#"{"interfaces": [{"name": "eth0", "ip": "10.0.0.5", "netmask": "ffff::"}]}"#
+        )
+        .is_err());
+        assert!(parse(r#"{"mode": "merge", "interfaces": []}"#).is_err());
+    }
+}
@@ -0,0 +1,126 @@
+// Copyright 2018 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::{IfAddr, Interface};
+use alloc::vec::Vec;
+#[cfg(not(feature = "std"))]
+use core::net::IpAddr;
+#[cfg(feature = "std")]
+use std::net::IpAddr;
+
+/// Chainable filters over enumeration results.
+///
+/// Implemented for `[Interface]` and for `[&Interface]`, which every filter returns, so that
+/// filters can be chained:
+///
+/// ```
+/// use if_addrs::{IfAddr, Ifv4Addr, Interface, InterfaceSliceExt};
+/// use std::net::{IpAddr, Ipv4Addr};
+///
+/// let v4 = |ip, prefix_len: u32| {
+///     let netmask = Ipv4Addr::from(u32::MAX << (32 - prefix_len));
+///     IfAddr::V4(Ifv4Addr::new(ip, netmask, None))
+/// };
+/// let ifaces = [
+///     Interface::new("lo".to_string(), v4(Ipv4Addr::LOCALHOST, 8), None),
+///     Interface::new("eth0".to_string(), v4(Ipv4Addr::new(192, 0, 2, 1), 24), None),
+/// ];
+/// let ips = ifaces.v4().up().non_loopback().named("eth*").ips();
+/// assert_eq!(ips, [IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1))]);
+/// ```
+pub trait InterfaceSliceExt {
+    /// Keep the interfaces matching `predicate`.
+    fn matching<F: FnMut(&Interface) -> bool>(&self, predicate: F) -> Vec<&Interface>;
+
+    /// Keep the IPv4 addresses.
+    fn v4(&self) -> Vec<&Interface> {
+        self.matching(|iface| matches!(iface.addr, IfAddr::V4(_)))
+    }
+
+    /// Keep the IPv6 addresses.
+    fn v6(&self) -> Vec<&Interface> {
+        self.matching(|iface| matches!(iface.addr, IfAddr::V6(_)))
+    }
+
+    /// Keep the interfaces that are up, see [`Interface::is_up`].
+    fn up(&self) -> Vec<&Interface> {
+        self.matching(Interface::is_up)
+    }
+
+    /// Drop the loopback addresses.
+    fn non_loopback(&self) -> Vec<&Interface> {
+        self.matching(|iface| !iface.is_loopback())
+    }
+
+    /// Keep the interfaces whose name matches `pattern`, in which `*` matches any run of
+    /// characters, e.g. `eth*`.
+    fn named(&self, pattern: &str) -> Vec<&Interface> {
+        self.matching(|iface| glob_matches(pattern, &iface.name))
+    }
+
+    /// Get the IP addresses.
+    fn ips(&self) -> Vec<IpAddr>;
+}
+
+impl InterfaceSliceExt for [Interface] {
+    fn matching<F: FnMut(&Interface) -> bool>(&self, mut predicate: F) -> Vec<&Interface> {
+        self.iter().filter(|iface| predicate(iface)).collect()
+    }
+
+    fn ips(&self) -> Vec<IpAddr> {
+        self.iter().map(Interface::ip).collect()
+    }
+}
+
+impl InterfaceSliceExt for [&Interface] {
+    fn matching<F: FnMut(&Interface) -> bool>(&self, mut predicate: F) -> Vec<&Interface> {
+        self.iter()
+            .copied()
+            .filter(|iface| predicate(iface))
+            .collect()
+    }
+
+    fn ips(&self) -> Vec<IpAddr> {
+        self.iter().map(|iface| iface.ip()).collect()
+    }
+}
+
+fn glob_matches(pattern: &str, name: &str) -> bool {
+    match pattern.find('*') {
+        None => pattern == name,
+        Some(star) => {
+            let (prefix, rest) = (&pattern[..star], &pattern[star + 1..]);
+            if !name.starts_with(prefix) {
+                return false;
+            }
+            let name = &name[prefix.len()..];
+            // Let the star swallow ever more characters until the rest matches.
+            name.char_indices()
+                .map(|(i, _)| i)
+                .chain(Some(name.len()))
+                .any(|i| glob_matches(rest, &name[i..]))
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::glob_matches;
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches("eth0", "eth0"));
+        assert!(!glob_matches("eth0", "eth01"));
+        assert!(glob_matches("eth*", "eth0"));
+        assert!(glob_matches("eth*", "eth"));
+        assert!(glob_matches("*0", "wlan0"));
+        assert!(glob_matches("w*n*", "wlan0"));
+        assert!(!glob_matches("eth*", "wlan0"));
+    }
+}
@@ -0,0 +1,124 @@
+// Copyright 2018 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::{IfAddr, Interface};
+use std::io;
+use std::net::IpAddr;
+
+/// A network interface together with all of its addresses, as returned by
+/// [`get_interfaces`].
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[non_exhaustive]
+pub struct NetworkInterface {
+    /// The name of the interface.
+    pub name: String,
+    /// The index of the interface, if it could be determined.
+    pub index: Option<u32>,
+    /// The raw `IFF_*` flags of the interface on Unix platforms. `None` on Windows.
+    pub flags: Option<u32>,
+    /// The addresses of the interface, in the order the operating system reported them.
+    pub addrs: Vec<IfAddr>,
+}
+
+impl NetworkInterface {
+    /// Get the IP addresses of the interface.
+    pub fn ips(&self) -> Vec<IpAddr> {
+        self.addrs.iter().map(IfAddr::ip).collect()
+    }
+}
+
+/// Get the network interfaces on this machine like [`get_if_addrs`](crate::get_if_addrs), but
+/// with one entry per interface holding all of its addresses, instead of one per address.
+///
+/// Addresses are grouped by interface index, or by name where the index is unknown. IPv4
+/// address labels such as `eth0:1` are listed under their link, `eth0`. Interfaces are listed
+/// in the order their first address was reported in.
+pub fn get_interfaces() -> io::Result<Vec<NetworkInterface>> {
+    Ok(group(crate::get_if_addrs()?))
+}
+
+fn group(ifaces: Vec<Interface>) -> Vec<NetworkInterface> {
+    let mut ret: Vec<NetworkInterface> = Vec::new();
+    for iface in ifaces {
+        let name = crate::link_name(&iface.name);
+        match ret
+            .iter_mut()
+            .find(|group| match (group.index, iface.index) {
+                (Some(a), Some(b)) => a == b,
+                _ => group.name == name,
+            }) {
+            Some(group) => {
+                group.index = group.index.or(iface.index);
+                group.flags = group.flags.or(iface.flags);
+                group.addrs.push(iface.addr);
+            }
+            None => ret.push(NetworkInterface {
+                name: name.to_string(),
+                index: iface.index,
+                flags: iface.flags,
+                addrs: vec![iface.addr],
+            }),
+        }
+    }
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::group;
+    use crate::{IfAddr, Ifv4Addr, Ifv6Addr, Interface};
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    #[test]
+    fn test_group() {
+        let v4 = |ip| IfAddr::V4(Ifv4Addr::new(ip, Ipv4Addr::new(255, 255, 255, 0), None));
+        let v6 = |ip| IfAddr::V6(Ifv6Addr::new(ip, Ipv6Addr::from(!0u128 << 64), None));
+        let ifaces = vec![
+            Interface::new("eth0".to_string(), v4(Ipv4Addr::new(192, 0, 2, 1)), Some(2)),
+            Interface::new("lo".to_string(), v4(Ipv4Addr::LOCALHOST), Some(1)),
+            Interface::new("eth0".to_string(), v6("2001:db8::1".parse().unwrap()), None),
+            Interface::new("eth0".to_string(), v4(Ipv4Addr::new(192, 0, 2, 2)), Some(2)),
+            Interface::new(
+                "eth0:1".to_string(),
+                v4(Ipv4Addr::new(192, 0, 2, 3)),
+                Some(2),
+            ),
+            Interface::new(
+                "wlan0:home".to_string(),
+                v4(Ipv4Addr::new(198, 51, 100, 1)),
+                None,
+            ),
+            Interface::new(
+                "wlan0".to_string(),
+                v4(Ipv4Addr::new(198, 51, 100, 2)),
+                Some(3),
+            ),
+        ];
+
+        let groups = group(ifaces);
+        assert_eq!(groups.len(), 3);
+        assert_eq!(groups[0].name, "eth0");
+        assert_eq!(groups[0].index, Some(2));
+        assert_eq!(
+            groups[0].ips(),
+            vec![
+                "192.0.2.1".parse::<std::net::IpAddr>().unwrap(),
+                "2001:db8::1".parse().unwrap(),
+                "192.0.2.2".parse().unwrap(),
+                "192.0.2.3".parse().unwrap(),
+            ]
+        );
+        assert_eq!(groups[1].name, "lo");
+        assert_eq!(groups[1].addrs.len(), 1);
+        // A label without an index joins its link by name.
+        assert_eq!(groups[2].name, "wlan0");
+        assert_eq!(groups[2].index, Some(3));
+        assert_eq!(groups[2].addrs.len(), 2);
+    }
+}
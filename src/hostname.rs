@@ -0,0 +1,111 @@
+// Copyright 2018 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use std::io;
+
+/// Get the host name of this host, without any domain.
+pub fn hostname() -> io::Result<String> {
+    os::hostname()
+}
+
+/// Get the fully qualified domain name of this host.
+///
+/// On POSIX platforms this is the canonical name the resolver returns for [`hostname`], so it
+/// fails if the host name does not resolve. On Windows it is the DNS name the computer is
+/// configured with.
+pub fn fqdn() -> io::Result<String> {
+    os::fqdn()
+}
+
+#[cfg(not(windows))]
+mod os {
+    use std::ffi::{CStr, CString};
+    use std::{io, mem, ptr};
+
+    #[allow(unsafe_code)]
+    pub fn hostname() -> io::Result<String> {
+        // Host names are at most 255 bytes, plus the terminating NUL.
+        let mut buf = [0 as libc::c_char; 256];
+        if unsafe { libc::gethostname(buf.as_mut_ptr(), buf.len()) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // The name may be truncated without a terminating NUL.
+        buf[buf.len() - 1] = 0;
+        Ok(unsafe { CStr::from_ptr(buf.as_ptr()) }
+            .to_string_lossy()
+            .into_owned())
+    }
+
+    #[allow(unsafe_code)]
+    pub fn fqdn() -> io::Result<String> {
+        let name = CString::new(hostname()?)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        let mut hints: libc::addrinfo = unsafe { mem::zeroed() };
+        hints.ai_family = libc::AF_UNSPEC;
+        hints.ai_flags = libc::AI_CANONNAME;
+        let mut res = ptr::null_mut();
+        match unsafe { libc::getaddrinfo(name.as_ptr(), ptr::null(), &hints, &mut res) } {
+            0 => {}
+            libc::EAI_SYSTEM => return Err(io::Error::last_os_error()),
+            err => {
+                let msg = unsafe { CStr::from_ptr(libc::gai_strerror(err)) };
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("cannot resolve {:?}: {}", name, msg.to_string_lossy()),
+                ));
+            }
+        }
+        let canonname = unsafe { (*res).ai_canonname };
+        let ret = if canonname.is_null() {
+            name.to_string_lossy().into_owned()
+        } else {
+            unsafe { CStr::from_ptr(canonname) }
+                .to_string_lossy()
+                .into_owned()
+        };
+        unsafe { libc::freeaddrinfo(res) };
+        Ok(ret)
+    }
+}
+
+#[cfg(windows)]
+mod os {
+    use std::io;
+    use winapi::shared::minwindef::DWORD;
+    use winapi::shared::winerror::ERROR_MORE_DATA;
+    use winapi::um::sysinfoapi::{
+        ComputerNameDnsFullyQualified, ComputerNameDnsHostname, GetComputerNameExW,
+        COMPUTER_NAME_FORMAT,
+    };
+
+    pub fn hostname() -> io::Result<String> {
+        computer_name(ComputerNameDnsHostname)
+    }
+
+    pub fn fqdn() -> io::Result<String> {
+        computer_name(ComputerNameDnsFullyQualified)
+    }
+
+    #[allow(unsafe_code)]
+    fn computer_name(format: COMPUTER_NAME_FORMAT) -> io::Result<String> {
+        let mut buf = vec![0u16; 256];
+        loop {
+            let mut len = buf.len() as DWORD;
+            if unsafe { GetComputerNameExW(format, buf.as_mut_ptr(), &mut len) } != 0 {
+                return Ok(String::from_utf16_lossy(&buf[..len as usize]));
+            }
+            let err = io::Error::last_os_error();
+            // On overflow `len` holds the required size, including the terminating NUL.
+            if err.raw_os_error() != Some(ERROR_MORE_DATA as i32) || len as usize <= buf.len() {
+                return Err(err);
+            }
+            buf.resize(len as usize, 0);
+        }
+    }
+}
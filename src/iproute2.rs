@@ -0,0 +1,420 @@
+// Copyright 2018 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Conversion to and from the JSON format printed by iproute2's `ip -j addr show`.
+//!
+//! The output is an array with one object per interface, each listing its addresses in
+//! `addr_info`. Details this crate does not know about, such as the MTU, qdisc or address
+//! lifetimes, are left out rather than made up, just as `ip` omits attributes the kernel did
+//! not report.
+
+use crate::{Cidr, IfAddr, Ifv4Addr, Ifv6Addr, Interface};
+use serde_json::{json, Map, Value};
+use std::convert::TryFrom;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr};
+
+// `IFF_*` flags in the order `ip` prints them, with their Linux values.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const FLAG_NAMES: &[(u32, &str)] = &[
+    (0x8, "LOOPBACK"),
+    (0x2, "BROADCAST"),
+    (0x10, "POINTOPOINT"),
+    (0x1000, "MULTICAST"),
+    (0x80, "NOARP"),
+    (0x200, "ALLMULTI"),
+    (0x100, "PROMISC"),
+    (0x400, "MASTER"),
+    (0x800, "SLAVE"),
+    (0x4, "DEBUG"),
+    (0x8000, "DYNAMIC"),
+    (0x4000, "AUTOMEDIA"),
+    (0x2000, "PORTSEL"),
+    (0x20, "NOTRAILERS"),
+    (0x1, "UP"),
+    (0x10000, "LOWER_UP"),
+    (0x20000, "DORMANT"),
+    (0x40000, "ECHO"),
+];
+
+// The subset of `IFF_*` flags with the same value on every Unix platform.
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+const FLAG_NAMES: &[(u32, &str)] = &[
+    (0x8, "LOOPBACK"),
+    (0x2, "BROADCAST"),
+    (0x10, "POINTOPOINT"),
+    (0x80, "NOARP"),
+    (0x200, "ALLMULTI"),
+    (0x100, "PROMISC"),
+    (0x4, "DEBUG"),
+    (0x1, "UP"),
+];
+
+/// Serialize `interfaces`, e.g. as returned by [`get_if_addrs`](crate::get_if_addrs), into
+/// the structure of `ip -j addr show`.
+///
+/// Addresses are grouped by interface name, in the order their interfaces first appear. IPv4
+/// aliases such as `eth0:1` are listed under their link, with the alias as `label`.
+pub fn to_ip_json(interfaces: &[Interface]) -> String {
+    let mut links: Vec<(&str, Vec<&Interface>)> = Vec::new();
+    for interface in interfaces {
+        let name = crate::link_name(&interface.name);
+        match links.iter_mut().find(|(link, _)| *link == name) {
+            Some((_, addrs)) => addrs.push(interface),
+            None => links.push((name, vec![interface])),
+        }
+    }
+    let links: Vec<Value> = links
+        .iter()
+        .map(|(name, addrs)| link(name, addrs))
+        .collect();
+    Value::Array(links).to_string()
+}
+
+/// Parse the output of `ip -j addr show` into one [`Interface`] per address, e.g. to replay
+/// the configuration of another machine.
+///
+/// IPv4 addresses are named after their `label`, as getifaddrs does, so aliases such as
+/// `eth0:1` keep their name. Links without addresses and attributes this crate has no field
+/// for are skipped. `ip` hides `RUNNING`, so it is derived from `operstate`, or from
+/// `LOWER_UP` when that is missing.
+pub fn from_ip_json(json: &str) -> io::Result<Vec<Interface>> {
+    parse(json).map_err(|msg| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid iproute2 JSON: {}", msg),
+        )
+    })
+}
+
+fn parse(json: &str) -> Result<Vec<Interface>, String> {
+    let root: Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    let links = root
+        .as_array()
+        .ok_or_else(|| "expected an array of links".to_string())?;
+
+    let mut interfaces = Vec::new();
+    for link in links {
+        let name =
+            string_field(link, "ifname")?.ok_or_else(|| "link without `ifname`".to_string())?;
+        let index = u32_field(link, "ifindex")?;
+        let flags = match link.get("flags") {
+            None | Some(Value::Null) => None,
+            Some(Value::Array(names)) => Some(parse_flags(
+                names,
+                string_field(link, "operstate")?.as_deref(),
+            )),
+            Some(_) => return Err("`flags` must be an array".to_string()),
+        };
+        let group = match string_field(link, "group")?.as_deref() {
+            None => None,
+            Some("default") => Some(0),
+            Some(group) => Some(
+                group
+                    .parse()
+                    .map_err(|_| format!("unknown group `{}`", group))?,
+            ),
+        };
+        let txqueuelen = u32_field(link, "txqlen")?;
+        let alias = string_field(link, "ifalias")?;
+
+        let addr_info = match link.get("addr_info") {
+            None | Some(Value::Null) => continue,
+            Some(Value::Array(addr_info)) => addr_info,
+            Some(_) => return Err(format!("`{}` has a non-array `addr_info`", name)),
+        };
+        for info in addr_info {
+            let addr = match parse_addr(info)? {
+                Some(addr) => addr,
+                None => continue,
+            };
+            let label = match addr {
+                IfAddr::V4(_) => string_field(info, "label")?,
+                IfAddr::V6(_) => None,
+            };
+            let mut interface = Interface::new(label.unwrap_or_else(|| name.clone()), addr, index);
+            interface.flags = flags;
+            interface.group = group;
+            interface.txqueuelen = txqueuelen;
+            interface.alias = alias.clone();
+            interfaces.push(interface);
+        }
+    }
+    Ok(interfaces)
+}
+
+/// Parse one `addr_info` entry, skipping families other than `inet` and `inet6`.
+fn parse_addr(info: &Value) -> Result<Option<IfAddr>, String> {
+    match string_field(info, "family")?.as_deref() {
+        Some("inet") | Some("inet6") => {}
+        _ => return Ok(None),
+    }
+    let local =
+        string_field(info, "local")?.ok_or_else(|| "address without `local`".to_string())?;
+    let ip: IpAddr = local
+        .parse()
+        .map_err(|_| format!("invalid address `{}`", local))?;
+    let netmask = u32_field(info, "prefixlen")?
+        .and_then(|prefix_len| u8::try_from(prefix_len).ok())
+        .and_then(|prefix_len| Cidr::new(ip, prefix_len))
+        .ok_or_else(|| format!("missing or invalid `prefixlen` for `{}`", ip))?
+        .netmask();
+
+    Ok(Some(match (ip, netmask) {
+        (IpAddr::V4(ip), IpAddr::V4(netmask)) => {
+            let broadcast = match string_field(info, "broadcast")? {
+                Some(broadcast) => Some(
+                    broadcast
+                        .parse::<Ipv4Addr>()
+                        .map_err(|_| format!("invalid address `{}`", broadcast))?,
+                ),
+                None => None,
+            };
+            IfAddr::V4(Ifv4Addr {
+                ip,
+                netmask,
+                broadcast,
+            })
+        }
+        (IpAddr::V6(ip), IpAddr::V6(netmask)) => IfAddr::V6(Ifv6Addr {
+            ip,
+            netmask,
+            broadcast: None,
+        }),
+        _ => unreachable!("netmask has the family of the address"),
+    }))
+}
+
+fn parse_flags(names: &[Value], operstate: Option<&str>) -> u32 {
+    let mut flags = names
+        .iter()
+        .filter_map(Value::as_str)
+        .filter_map(|name| FLAG_NAMES.iter().find(|&&(_, known)| known == name))
+        .fold(0, |flags, &(flag, _)| flags | flag);
+    let running = match operstate {
+        Some(operstate) => operstate == "UP" || operstate == "UNKNOWN",
+        None => names.iter().any(|name| name.as_str() == Some("LOWER_UP")),
+    };
+    if running {
+        flags |= crate::IFF_RUNNING;
+    }
+    flags
+}
+
+fn string_field(entry: &Value, key: &str) -> Result<Option<String>, String> {
+    match entry.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(s)) => Ok(Some(s.clone())),
+        Some(_) => Err(format!("`{}` must be a string", key)),
+    }
+}
+
+fn u32_field(entry: &Value, key: &str) -> Result<Option<u32>, String> {
+    match entry.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(value) => value
+            .as_u64()
+            .and_then(|value| u32::try_from(value).ok())
+            .map(Some)
+            .ok_or_else(|| format!("`{}` must be a 32-bit unsigned integer", key)),
+    }
+}
+
+fn link(name: &str, addrs: &[&Interface]) -> Value {
+    let first = addrs[0];
+    let mut link = Map::new();
+    if let Some(index) = first.index {
+        link.insert("ifindex".to_string(), json!(index));
+    }
+    link.insert("ifname".to_string(), json!(name));
+    if let Some(flags) = first.flags {
+        link.insert("flags".to_string(), json!(flag_names(flags)));
+    }
+    if let Some(group) = first.group {
+        let group = match group {
+            0 => "default".to_string(),
+            group => group.to_string(),
+        };
+        link.insert("group".to_string(), json!(group));
+    }
+    if let Some(txqueuelen) = first.txqueuelen {
+        link.insert("txqlen".to_string(), json!(txqueuelen));
+    }
+    if let Some(ref alias) = first.alias {
+        link.insert("ifalias".to_string(), json!(alias));
+    }
+    let addr_info: Vec<Value> = addrs.iter().map(|iface| addr_info(iface)).collect();
+    link.insert("addr_info".to_string(), Value::Array(addr_info));
+    Value::Object(link)
+}
+
+fn flag_names(flags: u32) -> Vec<&'static str> {
+    let mut names = Vec::new();
+    // Like `ip`, call out links that are up without a carrier.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        if flags & crate::IFF_UP != 0 && flags & 0x10000 == 0 {
+            names.push("NO-CARRIER");
+        }
+    }
+    names.extend(
+        FLAG_NAMES
+            .iter()
+            .filter(|&&(flag, _)| flags & flag != 0)
+            .map(|&(_, name)| name),
+    );
+    names
+}
+
+fn addr_info(interface: &Interface) -> Value {
+    let mut info = Map::new();
+    let family = match interface.addr {
+        IfAddr::V4(_) => "inet",
+        IfAddr::V6(_) => "inet6",
+    };
+    info.insert("family".to_string(), json!(family));
+    info.insert("local".to_string(), json!(interface.ip().to_string()));
+    info.insert(
+        "prefixlen".to_string(),
+        json!(interface.addr.cidr().prefix_len()),
+    );
+    if let IfAddr::V4(ref addr) = interface.addr {
+        if let Some(broadcast) = addr.broadcast {
+            info.insert("broadcast".to_string(), json!(broadcast.to_string()));
+        }
+    }
+    info.insert("scope".to_string(), json!(scope(interface.ip())));
+    // `ip` only labels IPv4 addresses.
+    if let IfAddr::V4(_) = interface.addr {
+        info.insert("label".to_string(), json!(interface.name));
+    }
+    Value::Object(info)
+}
+
+fn scope(ip: IpAddr) -> &'static str {
+    match ip {
+        ip if ip.is_loopback() => "host",
+        ip if crate::is_link_local(ip) => "link",
+        _ => "global",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_ip_json, to_ip_json};
+    use crate::{IfAddr, Ifv4Addr, Ifv6Addr, Interface};
+    use serde_json::{json, Value};
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    const SAMPLE: &str = r#"[
+        {"ifindex":1,"ifname":"lo","flags":["LOOPBACK","UP","LOWER_UP"],"mtu":65536,
+         "qdisc":"noqueue","operstate":"UNKNOWN","group":"default","txqlen":1000,
+         "link_type":"loopback","address":"00:00:00:00:00:00","broadcast":"00:00:00:00:00:00",
+         "addr_info":[
+            {"family":"inet","local":"127.0.0.1","prefixlen":8,"scope":"host","label":"lo",
+             "valid_life_time":4294967295,"preferred_life_time":4294967295},
+            {"family":"inet6","local":"::1","prefixlen":128,"scope":"host",
+             "valid_life_time":4294967295,"preferred_life_time":4294967295}]},
+        {"ifindex":2,"ifname":"eth0","flags":["BROADCAST","MULTICAST","UP","LOWER_UP"],
+         "mtu":1500,"operstate":"UP","group":"default","txqlen":1000,"link_type":"ether",
+         "address":"52:54:00:12:34:56","broadcast":"ff:ff:ff:ff:ff:ff",
+         "addr_info":[
+            {"family":"inet","local":"192.168.1.10","prefixlen":24,"broadcast":"192.168.1.255",
+             "scope":"global","dynamic":true,"label":"eth0"},
+            {"family":"inet","local":"192.168.1.11","prefixlen":24,"scope":"global",
+             "secondary":true,"label":"eth0:1"},
+            {"family":"inet6","local":"fe80::5054:ff:fe12:3456","prefixlen":64,"scope":"link"}]},
+        {"ifindex":3,"ifname":"eth1","flags":["NO-CARRIER","BROADCAST","MULTICAST","UP"],
+         "mtu":1500,"operstate":"DOWN","group":"default","txqlen":1000,"addr_info":[]}
+    ]"#;
+
+    #[test]
+    fn test_from_ip_json() {
+        let interfaces = from_ip_json(SAMPLE).unwrap();
+        let names: Vec<&str> = interfaces.iter().map(|i| i.name.as_str()).collect();
+        assert_eq!(vec!["lo", "lo", "eth0", "eth0:1", "eth0"], names);
+        assert!(interfaces.iter().all(|i| i.is_up()));
+        assert_eq!(Some(2), interfaces[2].index);
+        match interfaces[2].addr {
+            IfAddr::V4(ref addr) => {
+                assert_eq!(Ipv4Addr::new(255, 255, 255, 0), addr.netmask);
+                assert_eq!(Some(Ipv4Addr::new(192, 168, 1, 255)), addr.broadcast);
+            }
+            IfAddr::V6(_) => panic!("expected an IPv4 address"),
+        }
+        assert_eq!(64, interfaces[4].addr.cidr().prefix_len());
+
+        assert!(from_ip_json(r#"[{"ifindex":1}]"#).is_err());
+        assert!(from_ip_json(r#"{"ifname":"lo"}"#).is_err());
+    }
+
+    // The flag names differ from `ip` elsewhere, so pin the Linux output.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn test_to_ip_json() {
+        let netmask = Ipv4Addr::new(255, 255, 255, 0);
+        let broadcast = Some(Ipv4Addr::new(192, 168, 1, 255));
+        let addr = IfAddr::V4(Ifv4Addr::new(
+            Ipv4Addr::new(192, 168, 1, 10),
+            netmask,
+            broadcast,
+        ));
+        let mut eth0 = Interface::new("eth0".to_string(), addr, Some(2));
+        // UP | BROADCAST | MULTICAST | LOWER_UP
+        eth0.flags = Some(0x11003);
+        eth0.group = Some(0);
+        eth0.txqueuelen = Some(1000);
+        let addr = IfAddr::V4(Ifv4Addr::new(Ipv4Addr::new(192, 168, 1, 11), netmask, None));
+        let eth0_1 = Interface {
+            name: "eth0:1".to_string(),
+            addr,
+            ..eth0.clone()
+        };
+        let addr = IfAddr::V6(Ifv6Addr::new(
+            "fe80::5054:ff:fe12:3456".parse().unwrap(),
+            Ipv6Addr::from(!0u128 << 64),
+            None,
+        ));
+        let eth0_v6 = Interface {
+            addr,
+            ..eth0.clone()
+        };
+        // Up without a carrier, and without the details `ip` would omit either.
+        let addr = IfAddr::V4(Ifv4Addr::new(Ipv4Addr::new(10, 0, 0, 1), netmask, None));
+        let mut eth1 = Interface::new("eth1".to_string(), addr, Some(3));
+        eth1.flags = Some(0x1);
+
+        let json: Value =
+            serde_json::from_str(&to_ip_json(&[eth0, eth1, eth0_v6, eth0_1])).unwrap();
+        assert_eq!(
+            json,
+            json!([
+                {"ifindex":2,"ifname":"eth0","flags":["BROADCAST","MULTICAST","UP","LOWER_UP"],
+                 "group":"default","txqlen":1000,
+                 "addr_info":[
+                    {"family":"inet","local":"192.168.1.10","prefixlen":24,
+                     "broadcast":"192.168.1.255","scope":"global","label":"eth0"},
+                    {"family":"inet6","local":"fe80::5054:ff:fe12:3456","prefixlen":64,
+                     "scope":"link"},
+                    {"family":"inet","local":"192.168.1.11","prefixlen":24,"scope":"global",
+                     "label":"eth0:1"}]},
+                {"ifindex":3,"ifname":"eth1","flags":["NO-CARRIER","UP"],
+                 "addr_info":[
+                    {"family":"inet","local":"10.0.0.1","prefixlen":24,"scope":"global",
+                     "label":"eth1"}]}
+            ])
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let interfaces = from_ip_json(SAMPLE).unwrap();
+        assert_eq!(interfaces, from_ip_json(&to_ip_json(&interfaces)).unwrap());
+    }
+}
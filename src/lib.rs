@@ -7,34 +7,472 @@
 // specific language governing permissions and limitations relating to use of the SAFE Network
 // Software.
 
-#[cfg(not(windows))]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+mod cidr;
+#[cfg(feature = "config")]
+pub mod config;
+#[cfg(all(feature = "std", windows))]
+pub mod connectivity;
+#[cfg(feature = "std")]
+pub mod driver;
+#[cfg(all(feature = "std", feature = "env-override"))]
+mod env_override;
+mod ext;
+#[cfg(feature = "std")]
+mod group;
+#[cfg(feature = "std")]
+mod hostname;
+#[cfg(feature = "serde")]
+pub mod iproute2;
+#[cfg(all(feature = "std", windows))]
+pub mod luid;
+mod mac;
+#[cfg(all(feature = "std", target_os = "macos"))]
+mod macos;
+#[cfg(all(feature = "std", windows))]
+pub mod mib;
+#[cfg(feature = "std")]
+mod names;
+#[cfg(feature = "std")]
+mod nat64;
+#[cfg(all(feature = "std", feature = "rustix", target_os = "linux"))]
+mod netlink;
+#[cfg(all(feature = "std", target_os = "linux"))]
+pub mod netns;
+#[cfg(all(
+    feature = "network-framework",
+    any(target_os = "macos", target_os = "ios")
+))]
+pub mod network_path;
+#[cfg(all(feature = "nix", unix))]
+mod nix_compat;
+#[cfg(all(
+    target_os = "linux",
+    any(
+        all(feature = "std", feature = "rustix"),
+        feature = "wireless",
+        feature = "config"
+    )
+))]
+mod nlmsg;
+#[cfg(feature = "std")]
+mod options;
+#[cfg(feature = "oui")]
+mod oui;
+#[cfg(all(
+    feature = "std",
+    not(any(windows, all(feature = "rustix", target_os = "linux")))
+))]
 mod posix;
+#[cfg(all(feature = "std", any(target_os = "linux", target_os = "android")))]
+mod procfs;
+mod rfc6724;
+#[cfg(feature = "std")]
+pub mod routes;
+#[cfg(all(feature = "std", not(all(feature = "rustix", target_os = "linux"))))]
 mod sockaddr;
-#[cfg(windows)]
+#[cfg(feature = "socket2")]
+mod socket;
+#[cfg(feature = "std")]
+mod source;
+#[cfg(all(feature = "udev", target_os = "linux"))]
+pub mod udev;
+#[cfg(feature = "std")]
+mod wait;
+#[cfg(feature = "std")]
+mod warning;
+#[cfg(all(feature = "std", windows))]
 mod windows;
+#[cfg(feature = "wireless")]
+pub mod wireless;
+#[cfg(feature = "std")]
+pub mod zone;
 
+pub use crate::cidr::{Cidr, ParseCidrError};
+#[cfg(all(feature = "std", feature = "env-override"))]
+pub use crate::env_override::OVERRIDE_ENV_VAR;
+pub use crate::ext::InterfaceSliceExt;
+#[cfg(feature = "std")]
+pub use crate::group::{get_interfaces, NetworkInterface};
+#[cfg(feature = "std")]
+pub use crate::hostname::{fqdn, hostname};
+pub use crate::mac::{Hyphenated, MacAddr, ParseMacAddrError};
+#[cfg(feature = "std")]
+pub use crate::names::{get_if_names, interface_count, interface_exists};
+#[cfg(feature = "std")]
+pub use crate::nat64::nat64_prefix;
+#[cfg(feature = "std")]
+use crate::options::Lookups;
+#[cfg(feature = "std")]
+pub use crate::options::{get_if_addrs_with_options, IfAddrsOptions};
+#[cfg(feature = "std")]
+pub use crate::rfc6724::{connection_candidates, sort_destination_addrs};
+pub use crate::rfc6724::{connection_candidates_with, sort_destination_addrs_with};
+#[cfg(all(feature = "socket2", feature = "std"))]
+pub use crate::socket::MulticastExt;
+#[cfg(feature = "std")]
+pub use crate::source::{clear_source, set_source, IfAddrsSource, OsSource};
+#[cfg(feature = "std")]
+pub use crate::wait::{
+    wait_for_address, wait_for_interface, wait_for_network_online, InterfaceWatcher,
+    OnlineRequirements,
+};
+#[cfg(feature = "std")]
+pub use crate::warning::{get_if_addrs_with_warnings, IfAddrsWarning, IfAddrsWarningKind};
+use alloc::string::String;
+use alloc::vec::Vec;
+#[cfg(not(feature = "std"))]
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use core::time::Duration;
+#[cfg(feature = "std")]
 use std::io;
+#[cfg(feature = "std")]
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 
+/// The name, index and hardware address of an interface, as reported by the backends.
+#[cfg(feature = "std")]
+type HwAddrEntry = (String, Option<u32>, [u8; 6]);
+
+/// The name of the link an address label such as `eth0:1` belongs to.
+#[cfg(feature = "std")]
+pub(crate) fn link_name(label: &str) -> &str {
+    label.split(':').next().unwrap_or(label)
+}
+
+// `IFF_*` flags with the same value on every Unix platform.
+const IFF_UP: u32 = 0x1;
+const IFF_RUNNING: u32 = 0x40;
+const IFF_NOARP: u32 = 0x80;
+const IFF_PROMISC: u32 = 0x100;
+const IFF_ALLMULTI: u32 = 0x200;
+
 /// Details about an interface on this host.
+///
+/// New fields may be added in minor releases, so instances outside this crate are built with
+/// [`Interface::new`] and the remaining fields assigned afterwards.
+///
+/// The `lean` feature leaves the fields that take extra lookups `None` rather than removing
+/// them, so that enabling it in one crate does not break another in the same build.
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[non_exhaustive]
 pub struct Interface {
-    /// The name of the interface.
+    /// The name of the interface. Names that are not valid UTF-8 are converted lossily; the
+    /// original bytes are then kept in [`raw_name`](Interface::raw_name).
     pub name: String,
+    /// The name of the interface as the bytes the operating system reported, if they are not
+    /// valid UTF-8. Only Unix platforms allow such names. See [`Interface::name_os`].
+    pub raw_name: Option<Vec<u8>>,
     /// The address details of the interface.
     pub addr: IfAddr,
+    /// The index of the interface, if it could be determined.
+    pub index: Option<u32>,
+    /// The SystemConfiguration type of the interface on macOS, such as `IEEE80211` or
+    /// `Ethernet`, if asked for with [`IfAddrsOptions::system_configuration`]. Always `None` on
+    /// other platforms.
+    pub interface_type: Option<String>,
+    /// The localized display name of the interface on macOS, such as `Wi-Fi` or
+    /// `Thunderbolt Bridge`, if asked for with [`IfAddrsOptions::system_configuration`]. Always
+    /// `None` on other platforms.
+    pub display_name: Option<String>,
+    /// The alias an administrator gave the interface on Linux, e.g. with
+    /// `ip link set dev eth0 alias "uplink to sw1"`. Reported by the `rustix` backend, and
+    /// otherwise if asked for with [`IfAddrsOptions::link_details`]. `None` if unset and on
+    /// other platforms.
+    pub alias: Option<String>,
+    /// The transmit queue length of the interface on Linux, reported like
+    /// [`alias`](Interface::alias). `None` on other platforms.
+    pub txqueuelen: Option<u32>,
+    /// The interface group on Linux, as set with `ip link set dev eth0 group 5` and reported
+    /// like [`alias`](Interface::alias). Interfaces start in group `0`. `None` on other
+    /// platforms.
+    pub group: Option<u32>,
+    /// Whether the operating system reports the interface as a software device rather than
+    /// physical hardware, if known. On Linux only if asked for with
+    /// [`IfAddrsOptions::link_details`]. See [`Interface::is_virtual`].
+    pub virtual_device: Option<bool>,
+    /// Whether the operating system reports the interface as a VPN-style tunnel, if known.
+    /// On Linux only if asked for with [`IfAddrsOptions::link_details`]. See
+    /// [`Interface::is_vpn_like`].
+    pub vpn_device: Option<bool>,
+    /// Whether the operating system reports the interface as a WiFi link, on Windows, and on
+    /// Linux if asked for with [`IfAddrsOptions::link_details`]. See
+    /// [`Interface::is_wireless`].
+    pub wireless_device: Option<bool>,
+    /// Whether the operating system reports the interface as a cellular modem, on Windows,
+    /// and on Linux if asked for with [`IfAddrsOptions::link_details`]. See
+    /// [`Interface::is_cellular`].
+    pub cellular_device: Option<bool>,
+    /// An identifier of the adapter behind the interface that stays the same across reboots
+    /// and renames, for persisting a reference to it: the adapter GUID on Windows, the sysfs
+    /// path of the device on Linux, e.g. `/devices/pci0000:00/0000:00:1f.6`, if asked for
+    /// with [`IfAddrsOptions::link_details`], and the hardware address SystemConfiguration
+    /// reports for the adapter on macOS, if asked for with
+    /// [`IfAddrsOptions::system_configuration`]. `None` for software devices on Linux and
+    /// macOS, and on other platforms.
+    pub adapter_id: Option<String>,
+    /// The raw `IFF_*` flags of the interface on Unix platforms. On Linux `IFF_PROMISC` is also
+    /// set while the promiscuity count is non-zero, e.g. because a packet capture holds the
+    /// device in promiscuous mode; with getifaddrs only if asked for with
+    /// [`IfAddrsOptions::link_details`]. `None` on Windows.
+    pub flags: Option<u32>,
+    /// Whether the link layer detects a carrier, e.g. a plugged-in cable, regardless of the
+    /// operational state. Reported on Windows, and on Linux like [`alias`](Interface::alias);
+    /// `None` elsewhere, and on Linux for interfaces that are administratively down.
+    pub has_carrier: Option<bool>,
+    /// The negotiated duplex mode of an Ethernet link on Linux, as reported by ethtool, if
+    /// asked for with [`IfAddrsOptions::link_details`]. `None` if the link is down or not
+    /// Ethernet, and on other platforms, as Windows only exposes it through driver-specific
+    /// settings.
+    pub duplex: Option<Duplex>,
+    /// Whether IPv4 is enabled on the adapter on Windows, which tells an adapter that has no
+    /// IPv4 address yet apart from one with IPv4 turned off. `None` on other platforms.
+    pub ipv4_enabled: Option<bool>,
+    /// Whether IPv6 is enabled on the adapter on Windows. `None` on other platforms.
+    pub ipv6_enabled: Option<bool>,
+    /// Whether the adapter obtains its IPv4 configuration through DHCP on Windows. `None` on
+    /// other platforms. See [`Interface::is_ipv4_autoconfigured`].
+    pub dhcpv4_enabled: Option<bool>,
+    /// When the address was added, as time since boot with a resolution of 10ms. Only
+    /// reported by the netlink backend of the `rustix` feature on Linux.
+    pub addr_created: Option<Duration>,
+    /// When the address was last updated, e.g. when its lifetimes were refreshed by a router
+    /// advertisement or DHCP renewal, as time since boot. Reported like
+    /// [`addr_created`](Interface::addr_created).
+    pub addr_updated: Option<Duration>,
 }
 
 impl Interface {
+    /// Create an interface with only the details every backend provides, leaving all other
+    /// fields `None`.
+    pub fn new(name: String, addr: IfAddr, index: Option<u32>) -> Self {
+        Interface {
+            name,
+            raw_name: None,
+            addr,
+            index,
+            interface_type: None,
+            display_name: None,
+            alias: None,
+            txqueuelen: None,
+            group: None,
+            virtual_device: None,
+            vpn_device: None,
+            wireless_device: None,
+            cellular_device: None,
+            adapter_id: None,
+            flags: None,
+            has_carrier: None,
+            duplex: None,
+            ipv4_enabled: None,
+            ipv6_enabled: None,
+            dhcpv4_enabled: None,
+            addr_created: None,
+            addr_updated: None,
+        }
+    }
+
     /// Check whether this is a loopback interface.
     pub fn is_loopback(&self) -> bool {
         self.addr.is_loopback()
     }
 
+    /// Get the name of the interface without the lossy conversion of
+    /// [`name`](Interface::name), e.g. to pass it back to the operating system.
+    #[cfg(feature = "std")]
+    pub fn name_os(&self) -> std::ffi::OsString {
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStringExt;
+            if let Some(ref raw_name) = self.raw_name {
+                return std::ffi::OsString::from_vec(raw_name.clone());
+            }
+        }
+        std::ffi::OsString::from(&self.name)
+    }
+
     /// Get the IP address of this interface.
     pub fn ip(&self) -> IpAddr {
         self.addr.ip()
     }
+
+    /// Get the netmask of this interface.
+    pub fn netmask(&self) -> IpAddr {
+        self.addr.netmask()
+    }
+
+    /// Get the broadcast address of this interface, if it has one.
+    pub fn broadcast(&self) -> Option<IpAddr> {
+        self.addr.broadcast()
+    }
+
+    /// Get the prefix length of this interface. See [`IfAddr::prefixlen`].
+    pub fn prefixlen(&self) -> u8 {
+        self.addr.prefixlen()
+    }
+
+    /// Check whether `ip` is on the same subnet as this interface, i.e. directly reachable
+    /// through it. See [`IfAddr::same_subnet`].
+    pub fn same_subnet(&self, ip: IpAddr) -> bool {
+        self.addr.same_subnet(ip)
+    }
+
+    /// Best-effort check whether this interface is virtual: loopback, veth pairs, bridges,
+    /// tunnels, container networks or hypervisor adapters rather than a physical NIC.
+    ///
+    /// On Linux this is whether the device lives under `/sys/devices/virtual`, if asked for with
+    /// [`IfAddrsOptions::link_details`], and on Windows it is derived from the adapter type and
+    /// description. Elsewhere, or if that failed, the interface name is matched against common
+    /// virtual interface prefixes.
+    pub fn is_virtual(&self) -> bool {
+        const PREFIXES: [&str; 12] = [
+            "lo", "veth", "docker", "br-", "virbr", "vnet", "vmnet", "vboxnet", "tun", "tap",
+            "utun", "bridge",
+        ];
+        match self.virtual_device {
+            Some(virtual_device) => virtual_device,
+            None => PREFIXES.iter().any(|prefix| self.name.starts_with(prefix)),
+        }
+    }
+
+    /// Best-effort check whether this interface is a VPN-style tunnel: WireGuard, tun/tap,
+    /// `utun`, PPP or a TAP-Windows/Wintun adapter.
+    ///
+    /// On Linux the device type is read from sysfs if asked for with
+    /// [`IfAddrsOptions::link_details`], and on Windows it is derived from the adapter type and
+    /// description. Elsewhere, or if that failed, the interface name is matched against common
+    /// tunnel prefixes.
+    pub fn is_vpn_like(&self) -> bool {
+        const PREFIXES: [&str; 6] = ["wg", "tun", "tap", "utun", "ppp", "ipsec"];
+        match self.vpn_device {
+            Some(vpn_device) => vpn_device,
+            None => PREFIXES.iter().any(|prefix| self.name.starts_with(prefix)),
+        }
+    }
+
+    /// Check whether this is a WiFi interface.
+    ///
+    /// On Linux this is read from sysfs if asked for with [`IfAddrsOptions::link_details`], on
+    /// Windows it is derived from the adapter type and on macOS from the SystemConfiguration
+    /// type, if asked for with [`IfAddrsOptions::system_configuration`]. Interface names are
+    /// not consulted, as predictable names and USB dongles make prefixes such as `wl`
+    /// unreliable.
+    pub fn is_wireless(&self) -> bool {
+        match self.wireless_device {
+            Some(wireless_device) => wireless_device,
+            None => self.interface_type.as_deref() == Some("IEEE80211"),
+        }
+    }
+
+    /// Best-effort check whether this is a cellular (WWAN) interface, usually a metered link.
+    ///
+    /// On Linux the device type is read from sysfs if asked for with
+    /// [`IfAddrsOptions::link_details`], and on Windows it is derived from the Mobile Broadband
+    /// adapter types. Elsewhere, or if that failed, the interface name is
+    /// matched against common modem prefixes such as `rmnet`, `wwan` and Apple's `pdp_ip`.
+    pub fn is_cellular(&self) -> bool {
+        const PREFIXES: [&str; 4] = ["rmnet", "wwan", "pdp_ip", "ccmni"];
+        match self.cellular_device {
+            Some(cellular_device) => cellular_device,
+            None => PREFIXES.iter().any(|prefix| self.name.starts_with(prefix)),
+        }
+    }
+
+    /// Check whether the interface is in promiscuous mode, i.e. receives all traffic on the link
+    /// rather than only that addressed to it. Always `false` if the flags are unknown.
+    pub fn is_promiscuous(&self) -> bool {
+        self.has_flag(IFF_PROMISC)
+    }
+
+    /// Check whether the interface does not use ARP, as on point-to-point tunnels. Always `false`
+    /// if the flags are unknown.
+    pub fn is_noarp(&self) -> bool {
+        self.has_flag(IFF_NOARP)
+    }
+
+    /// Check whether the interface receives all multicast traffic on the link. Always `false` if
+    /// the flags are unknown.
+    pub fn is_allmulti(&self) -> bool {
+        self.has_flag(IFF_ALLMULTI)
+    }
+
+    /// Check whether the interface is operationally up. On Unix platforms this requires both
+    /// `IFF_UP` and `IFF_RUNNING`. Without flags, as on Windows, an interface is up unless its
+    /// medium is known to be disconnected.
+    pub fn is_up(&self) -> bool {
+        match self.flags {
+            Some(flags) => flags & (IFF_UP | IFF_RUNNING) == IFF_UP | IFF_RUNNING,
+            None => self.has_carrier != Some(false),
+        }
+    }
+
+    /// Check whether this is a `169.254.0.0/16` address a DHCP adapter assigned itself because
+    /// no DHCP server answered. Always `false` if [`Interface::dhcpv4_enabled`] is unknown.
+    pub fn is_ipv4_autoconfigured(&self) -> bool {
+        match self.addr {
+            IfAddr::V4(ref ifv4_addr) => {
+                self.dhcpv4_enabled == Some(true) && ifv4_addr.ip.is_link_local()
+            }
+            IfAddr::V6(_) => false,
+        }
+    }
+
+    fn has_flag(&self, flag: u32) -> bool {
+        match self.flags {
+            Some(flags) => flags & flag != 0,
+            None => false,
+        }
+    }
+
+    /// Check whether this is one of the interfaces Apple platforms create for their own
+    /// plumbing rather than for user traffic: AirDrop/Wi-Fi Aware (`awdl*`, `llw*`),
+    /// VPN and system tunnels (`utun*`, `ipsec*`) and cellular PDP contexts (`pdp_ip*`).
+    ///
+    /// Apps presenting "your IP address" usually want to exclude these. The check is based on
+    /// the name only, so it gives the same answer on every platform.
+    pub fn is_apple_internal(&self) -> bool {
+        const PREFIXES: [&str; 5] = ["awdl", "llw", "utun", "ipsec", "pdp_ip"];
+        PREFIXES.iter().any(|prefix| {
+            self.name.starts_with(prefix)
+                && self.name[prefix.len()..]
+                    .bytes()
+                    .all(|b| b.is_ascii_digit())
+        })
+    }
+
+    /// The IPv6 transition technology of this interface's address, if any. Applications
+    /// usually prefer native IPv6 connectivity over these. See
+    /// [`Ifv6Addr::transition_technology`].
+    pub fn transition_technology(&self) -> Option<TransitionTechnology> {
+        match self.addr {
+            IfAddr::V4(_) => None,
+            IfAddr::V6(ref ifv6_addr) => ifv6_addr.transition_technology(),
+        }
+    }
+}
+
+/// The duplex mode of a link.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Duplex {
+    /// Both directions transmit at the same time.
+    Full,
+    /// The directions take turns, which on a switched network usually means the
+    /// autonegotiation went wrong.
+    Half,
+}
+
+/// An IPv6 transition technology, tunnelling IPv6 over IPv4.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum TransitionTechnology {
+    /// 6to4 (RFC 3056), with addresses in `2002::/16`.
+    SixToFour,
+    /// Teredo (RFC 4380), with addresses in `2001::/32`.
+    Teredo,
+    /// ISATAP (RFC 5214), with an interface identifier of `0:5efe` followed by the IPv4
+    /// address.
+    Isatap,
 }
 
 /// Details about the address of an interface on this host.
@@ -62,10 +500,51 @@ impl IfAddr {
             IfAddr::V6(ref ifv6_addr) => IpAddr::V6(ifv6_addr.ip),
         }
     }
+
+    /// Get the netmask of this interface address.
+    pub fn netmask(&self) -> IpAddr {
+        match *self {
+            IfAddr::V4(ref ifv4_addr) => IpAddr::V4(ifv4_addr.netmask),
+            IfAddr::V6(ref ifv6_addr) => IpAddr::V6(ifv6_addr.netmask),
+        }
+    }
+
+    /// Get the broadcast address of this interface address, if it has one.
+    pub fn broadcast(&self) -> Option<IpAddr> {
+        match *self {
+            IfAddr::V4(ref ifv4_addr) => ifv4_addr.broadcast.map(IpAddr::V4),
+            IfAddr::V6(ref ifv6_addr) => ifv6_addr.broadcast.map(IpAddr::V6),
+        }
+    }
+
+    /// Get the address and prefix length of this interface address.
+    pub fn cidr(&self) -> Cidr {
+        match *self {
+            IfAddr::V4(ref ifv4_addr) => ifv4_addr.cidr(),
+            IfAddr::V6(ref ifv6_addr) => ifv6_addr.cidr(),
+        }
+    }
+
+    /// Get the prefix length of this interface address, as in [`cidr()`](IfAddr::cidr).
+    pub fn prefixlen(&self) -> u8 {
+        self.cidr().prefix_len()
+    }
+
+    /// Check whether `ip` is on the same subnet as this address, applying the netmask.
+    ///
+    /// Addresses of the other family never match.
+    pub fn same_subnet(&self, ip: IpAddr) -> bool {
+        match (self, ip) {
+            (IfAddr::V4(ifv4_addr), IpAddr::V4(ip)) => ifv4_addr.same_subnet(ip),
+            (IfAddr::V6(ifv6_addr), IpAddr::V6(ip)) => ifv6_addr.same_subnet(ip),
+            _ => false,
+        }
+    }
 }
 
 /// Details about the ipv4 address of an interface on this host.
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[non_exhaustive]
 pub struct Ifv4Addr {
     /// The IP address of the interface.
     pub ip: Ipv4Addr,
@@ -76,14 +555,36 @@ pub struct Ifv4Addr {
 }
 
 impl Ifv4Addr {
+    /// Create an ipv4 interface address.
+    pub fn new(ip: Ipv4Addr, netmask: Ipv4Addr, broadcast: Option<Ipv4Addr>) -> Self {
+        Ifv4Addr {
+            ip,
+            netmask,
+            broadcast,
+        }
+    }
+
     /// Check whether this is a loopback address.
     pub fn is_loopback(&self) -> bool {
         self.ip.octets()[0] == 127
     }
+
+    /// Get the address and prefix length of this interface address. The prefix length counts
+    /// the leading one bits of the netmask.
+    pub fn cidr(&self) -> Cidr {
+        Cidr::saturating(IpAddr::V4(self.ip), u32::from(self.netmask).leading_ones())
+    }
+
+    /// Check whether `ip` is on the same subnet as this address, applying the netmask.
+    pub fn same_subnet(&self, ip: Ipv4Addr) -> bool {
+        let netmask = u32::from(self.netmask);
+        u32::from(self.ip) & netmask == u32::from(ip) & netmask
+    }
 }
 
 /// Details about the ipv6 address of an interface on this host.
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[non_exhaustive]
 pub struct Ifv6Addr {
     /// The IP address of the interface.
     pub ip: Ipv6Addr,
@@ -94,42 +595,111 @@ pub struct Ifv6Addr {
 }
 
 impl Ifv6Addr {
+    /// Create an ipv6 interface address.
+    pub fn new(ip: Ipv6Addr, netmask: Ipv6Addr, broadcast: Option<Ipv6Addr>) -> Self {
+        Ifv6Addr {
+            ip,
+            netmask,
+            broadcast,
+        }
+    }
+
     /// Check whether this is a loopback address.
     pub fn is_loopback(&self) -> bool {
         self.ip.segments() == [0, 0, 0, 0, 0, 0, 0, 1]
     }
+
+    /// Get the address and prefix length of this interface address. The prefix length counts
+    /// the leading one bits of the netmask.
+    pub fn cidr(&self) -> Cidr {
+        Cidr::saturating(IpAddr::V6(self.ip), u128::from(self.netmask).leading_ones())
+    }
+
+    /// Check whether `ip` is on the same subnet as this address, applying the netmask.
+    ///
+    /// Link-local (`fe80::/10`) addresses are only ever on-link with other link-local
+    /// addresses, regardless of the netmask, since that scope is attached to every link.
+    pub fn same_subnet(&self, ip: Ipv6Addr) -> bool {
+        let (local, other) = (
+            is_link_local(IpAddr::V6(self.ip)),
+            is_link_local(IpAddr::V6(ip)),
+        );
+        if local || other {
+            return local && other;
+        }
+        let netmask = u128::from(self.netmask);
+        u128::from(self.ip) & netmask == u128::from(ip) & netmask
+    }
+
+    /// Classify this address as a 6to4, Teredo or ISATAP one from its prefix and interface
+    /// identifier.
+    pub fn transition_technology(&self) -> Option<TransitionTechnology> {
+        let segments = self.ip.segments();
+        if segments[0] == 0x2002 {
+            Some(TransitionTechnology::SixToFour)
+        } else if segments[0] == 0x2001 && segments[1] == 0 {
+            Some(TransitionTechnology::Teredo)
+        } else if segments[4] & 0xfcff == 0 && segments[5] == 0x5efe {
+            // The universal/local and group bits may be set.
+            Some(TransitionTechnology::Isatap)
+        } else {
+            None
+        }
+    }
 }
 
-#[cfg(not(windows))]
+#[cfg(all(
+    feature = "std",
+    not(any(windows, all(feature = "rustix", target_os = "linux")))
+))]
 mod getifaddrs_posix {
-    use super::{IfAddr, Ifv4Addr, Ifv6Addr, Interface};
-    use crate::posix::{self as ifaddrs, IfAddrs};
-    use crate::sockaddr;
-    use std::ffi::CStr;
+    use super::{
+        HwAddrEntry, IfAddr, IfAddrsWarning, IfAddrsWarningKind, Ifv4Addr, Ifv6Addr, Interface,
+    };
+    use crate::options::Lookups;
+    use crate::posix::IfAddrs;
     use std::io;
     use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 
-    /// Return a vector of IP details for all the valid interfaces on this host.
+    /// Push the IP details of all the valid interfaces on this host onto `ret`, making the
+    /// optional `lookups` and adding any problems with individual entries to `warnings`.
     #[allow(unsafe_code)]
-    pub fn get_if_addrs() -> io::Result<Vec<Interface>> {
-        let mut ret = Vec::<Interface>::new();
+    #[cfg_attr(feature = "lean", allow(unused_variables))]
+    pub fn get_if_addrs(
+        lookups: Lookups,
+        ret: &mut Vec<Interface>,
+        warnings: &mut Vec<IfAddrsWarning>,
+    ) -> io::Result<()> {
         let ifaddrs = IfAddrs::new()?;
+        #[cfg(all(target_os = "macos", not(feature = "lean")))]
+        let sc_interfaces = if lookups.system_configuration {
+            crate::macos::sc_interfaces()
+        } else {
+            Default::default()
+        };
+        #[cfg(all(any(target_os = "linux", target_os = "android"), not(feature = "lean")))]
+        let mut links = crate::procfs::LinkCache::new(lookups);
 
         for ifaddr in ifaddrs.iter() {
-            let addr = match sockaddr::to_ipaddr(ifaddr.ifa_addr) {
-                None => continue,
+            let addr = match ifaddr.addr() {
+                None => {
+                    if ifaddr.addr_is_truncated() {
+                        warnings.push(IfAddrsWarning::new(
+                            Some(&ifaddr.name().to_string_lossy()),
+                            None,
+                            IfAddrsWarningKind::MalformedAddress,
+                        ));
+                    }
+                    continue;
+                }
                 Some(IpAddr::V4(ipv4_addr)) => {
-                    let netmask = match sockaddr::to_ipaddr(ifaddr.ifa_netmask) {
+                    let netmask = match ifaddr.netmask() {
                         Some(IpAddr::V4(netmask)) => netmask,
                         _ => Ipv4Addr::new(0, 0, 0, 0),
                     };
-                    let broadcast = if (ifaddr.ifa_flags & 2) != 0 {
-                        match ifaddrs::do_broadcast(&ifaddr) {
-                            Some(IpAddr::V4(broadcast)) => Some(broadcast),
-                            _ => None,
-                        }
-                    } else {
-                        None
+                    let broadcast = match ifaddr.broadcast() {
+                        Some(IpAddr::V4(broadcast)) => Some(broadcast),
+                        _ => None,
                     };
 
                     IfAddr::V4(Ifv4Addr {
@@ -139,17 +709,13 @@ mod getifaddrs_posix {
                     })
                 }
                 Some(IpAddr::V6(ipv6_addr)) => {
-                    let netmask = match sockaddr::to_ipaddr(ifaddr.ifa_netmask) {
+                    let netmask = match ifaddr.netmask() {
                         Some(IpAddr::V6(netmask)) => netmask,
                         _ => Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 0),
                     };
-                    let broadcast = if (ifaddr.ifa_flags & 2) != 0 {
-                        match ifaddrs::do_broadcast(&ifaddr) {
-                            Some(IpAddr::V6(broadcast)) => Some(broadcast),
-                            _ => None,
-                        }
-                    } else {
-                        None
+                    let broadcast = match ifaddr.broadcast() {
+                        Some(IpAddr::V6(broadcast)) => Some(broadcast),
+                        _ => None,
                     };
 
                     IfAddr::V6(Ifv6Addr {
@@ -160,192 +726,710 @@ mod getifaddrs_posix {
                 }
             };
 
-            let name = unsafe { CStr::from_ptr(ifaddr.ifa_name) }
-                .to_string_lossy()
-                .into_owned();
-            ret.push(Interface { name, addr });
+            let name = ifaddr.name().to_string_lossy().into_owned();
+            let raw_name = match ifaddr.name().to_str() {
+                Ok(_) => None,
+                Err(_) => Some(ifaddr.name().to_bytes().to_vec()),
+            };
+            if ifaddr.netmask().is_none() {
+                warnings.push(IfAddrsWarning::new(
+                    Some(&name),
+                    None,
+                    IfAddrsWarningKind::MissingNetmask,
+                ));
+            }
+            #[cfg(not(feature = "lean"))]
+            let index = if lookups.index {
+                match unsafe { libc::if_nametoindex(ifaddr.name().as_ptr()) } {
+                    0 => {
+                        warnings.push(IfAddrsWarning::new(
+                            Some(&name),
+                            None,
+                            IfAddrsWarningKind::IndexLookupFailed,
+                        ));
+                        None
+                    }
+                    index => Some(index),
+                }
+            } else {
+                None
+            };
+            #[cfg(feature = "lean")]
+            let index = None;
+            let mut iface = Interface::new(name, addr, index);
+            iface.raw_name = raw_name;
+            iface.flags = Some(ifaddr.flags());
+            #[cfg(all(target_os = "macos", not(feature = "lean")))]
+            {
+                if let Some(sc) = sc_interfaces.get(&iface.name) {
+                    iface.interface_type = sc.interface_type.clone();
+                    iface.display_name = sc.display_name.clone();
+                    iface.adapter_id = sc.hardware_address.clone();
+                }
+            }
+            #[cfg(all(any(target_os = "linux", target_os = "android"), not(feature = "lean")))]
+            if lookups.link_details {
+                // getifaddrs only reports IFF_PROMISC when it was requested explicitly, while
+                // sysfs shows the device flags, which also carry it while packet sockets raise
+                // the promiscuity count.
+                let link = links.get(&iface.name);
+                let promisc = link.flags.unwrap_or(0) & super::IFF_PROMISC;
+                iface.flags = iface.flags.map(|flags| flags | promisc);
+                iface.alias = link.alias.clone();
+                iface.txqueuelen = link.txqueuelen;
+                iface.group = link.group;
+                if lookups.status {
+                    iface.has_carrier = link.carrier;
+                }
+                link.apply_device(&mut iface);
+            }
+            ret.push(iface);
+        }
+
+        Ok(())
+    }
+
+    /// Return the name, index and 6-byte hardware address of every interface that has one.
+    pub fn get_hw_addrs() -> io::Result<Vec<HwAddrEntry>> {
+        let mut ret = Vec::new();
+        let ifaddrs = IfAddrs::new()?;
+
+        for ifaddr in ifaddrs.iter() {
+            if let Some((hw_addr, index)) = ifaddr.hwaddr() {
+                let name = ifaddr.name().to_string_lossy().into_owned();
+                let index = if index == 0 { None } else { Some(index) };
+                ret.push((name, index, hw_addr));
+            }
         }
 
         Ok(ret)
     }
 }
 
-/// Get a list of all the network interfaces on this machine along with their IP info.
-#[cfg(not(windows))]
-pub fn get_if_addrs() -> io::Result<Vec<Interface>> {
-    getifaddrs_posix::get_if_addrs()
+#[cfg(all(
+    feature = "std",
+    not(any(windows, all(feature = "rustix", target_os = "linux")))
+))]
+fn os_get_if_addrs_into(
+    lookups: Lookups,
+    ret: &mut Vec<Interface>,
+    warnings: &mut Vec<IfAddrsWarning>,
+) -> io::Result<()> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        let result = getifaddrs_posix::get_if_addrs(lookups, ret, warnings);
+        procfs::or_fallback(result, ret, lookups)
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    {
+        getifaddrs_posix::get_if_addrs(lookups, ret, warnings)
+    }
 }
 
-#[cfg(windows)]
-mod getifaddrs_windows {
-    use super::{IfAddr, Ifv4Addr, Ifv6Addr, Interface};
-    use crate::sockaddr;
-    use crate::windows::IfAddrs;
+#[cfg(all(
+    feature = "std",
+    not(any(windows, all(feature = "rustix", target_os = "linux")))
+))]
+fn os_get_hw_addrs() -> io::Result<Vec<HwAddrEntry>> {
+    getifaddrs_posix::get_hw_addrs()
+}
+
+#[cfg(all(feature = "std", feature = "rustix", target_os = "linux"))]
+mod getifaddrs_rustix {
+    use super::{HwAddrEntry, IfAddr, IfAddrsWarning, Ifv4Addr, Ifv6Addr, Interface};
+    use crate::netlink::IfAddrs;
+    use crate::options::Lookups;
     use std::io;
     use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+    use std::time::Duration;
 
-    /// Return a vector of IP details for all the valid interfaces on this host.
-    pub fn get_if_addrs() -> io::Result<Vec<Interface>> {
-        let mut ret = Vec::<Interface>::new();
+    /// Push the IP details of all the valid interfaces on this host onto `ret`, keeping the
+    /// details `lookups` asks for and adding any problems with individual entries to
+    /// `warnings`.
+    pub fn get_if_addrs(
+        lookups: Lookups,
+        ret: &mut Vec<Interface>,
+        warnings: &mut Vec<IfAddrsWarning>,
+    ) -> io::Result<()> {
         let ifaddrs = IfAddrs::new()?;
+        warnings.extend_from_slice(ifaddrs.warnings());
+        // Netlink already reports the index and carrier, so sysfs need not be asked for them.
+        #[cfg(not(feature = "lean"))]
+        let mut links = crate::procfs::LinkCache::new(Lookups {
+            index: false,
+            status: false,
+            ..lookups
+        });
 
         for ifaddr in ifaddrs.iter() {
-            for addr in ifaddr.unicast_addresses() {
-                let addr = match sockaddr::to_ipaddr(addr.address.lp_socket_address) {
-                    None => continue,
-                    Some(IpAddr::V4(ipv4_addr)) => {
-                        let mut item_netmask = Ipv4Addr::new(0, 0, 0, 0);
-                        let mut item_broadcast = None;
-
-                        // Search prefixes for a prefix matching addr
-                        'prefixloopv4: for prefix in ifaddr.prefixes() {
-                            let ipprefix = sockaddr::to_ipaddr(prefix.address.lp_socket_address);
-                            match ipprefix {
-                                Some(IpAddr::V4(ref a)) => {
-                                    let mut netmask: [u8; 4] = [0; 4];
-                                    for (n, netmask_elt) in netmask
-                                        .iter_mut()
-                                        .enumerate()
-                                        .take((prefix.prefix_length as usize + 7) / 8)
-                                    {
-                                        let x_byte = ipv4_addr.octets()[n];
-                                        let y_byte = a.octets()[n];
-                                        for m in 0..8 {
-                                            if (n * 8) + m > prefix.prefix_length as usize {
-                                                break;
-                                            }
-                                            let bit = 1 << m;
-                                            if (x_byte & bit) == (y_byte & bit) {
-                                                *netmask_elt |= bit;
-                                            } else {
-                                                continue 'prefixloopv4;
-                                            }
+            let addr = match ifaddr.address {
+                IpAddr::V4(ipv4_addr) => {
+                    let netmask = u32::MAX
+                        .checked_shl(32 - u32::from(ifaddr.prefixlen.min(32)))
+                        .unwrap_or(0);
+                    let broadcast = match ifaddr.broadcast {
+                        Some(IpAddr::V4(broadcast)) => Some(broadcast),
+                        _ => None,
+                    };
+
+                    IfAddr::V4(Ifv4Addr {
+                        ip: ipv4_addr,
+                        netmask: Ipv4Addr::from(netmask),
+                        broadcast,
+                    })
+                }
+                IpAddr::V6(ipv6_addr) => {
+                    // Ignore all fe80:: addresses as these are link locals
+                    if super::is_link_local(IpAddr::V6(ipv6_addr)) {
+                        continue;
+                    }
+                    let netmask = u128::MAX
+                        .checked_shl(128 - u32::from(ifaddr.prefixlen.min(128)))
+                        .unwrap_or(0);
+
+                    IfAddr::V6(Ifv6Addr {
+                        ip: ipv6_addr,
+                        netmask: Ipv6Addr::from(netmask),
+                        broadcast: None,
+                    })
+                }
+            };
+
+            let index = Some(ifaddr.index).filter(|_| lookups.index);
+            let mut iface = Interface::new(ifaddr.name.clone(), addr, index);
+            iface.raw_name = ifaddr.raw_name.clone();
+            if let Some(link) = ifaddrs.links().find(|link| link.index == ifaddr.index) {
+                iface.alias = link.alias.clone();
+                iface.txqueuelen = link.txqueuelen;
+                iface.group = link.group;
+                iface.flags = Some(if link.promiscuity.unwrap_or(0) > 0 {
+                    link.flags | super::IFF_PROMISC
+                } else {
+                    link.flags
+                });
+                if lookups.status {
+                    iface.has_carrier = link.carrier;
+                }
+            }
+            iface.addr_created = ifaddr.created.map(centiseconds);
+            iface.addr_updated = ifaddr.updated.map(centiseconds);
+            #[cfg(not(feature = "lean"))]
+            if lookups.link_details {
+                links.get(&iface.name).apply_device(&mut iface);
+            }
+            ret.push(iface);
+        }
+
+        Ok(())
+    }
+
+    fn centiseconds(stamp: u32) -> Duration {
+        Duration::from_millis(u64::from(stamp) * 10)
+    }
+
+    /// Return the name, index and 6-byte hardware address of every interface that has one.
+    pub fn get_hw_addrs() -> io::Result<Vec<HwAddrEntry>> {
+        let ifaddrs = IfAddrs::links_only()?;
+        Ok(ifaddrs
+            .links()
+            .filter_map(|link| Some((link.name.clone(), Some(link.index), link.hw_addr?)))
+            .collect())
+    }
+}
+
+#[cfg(all(feature = "std", feature = "rustix", target_os = "linux"))]
+fn os_get_if_addrs_into(
+    lookups: Lookups,
+    ret: &mut Vec<Interface>,
+    warnings: &mut Vec<IfAddrsWarning>,
+) -> io::Result<()> {
+    let result = getifaddrs_rustix::get_if_addrs(lookups, ret, warnings);
+    procfs::or_fallback(result, ret, lookups)
+}
+
+#[cfg(all(feature = "std", feature = "rustix", target_os = "linux"))]
+fn os_get_hw_addrs() -> io::Result<Vec<HwAddrEntry>> {
+    getifaddrs_rustix::get_hw_addrs()
+}
+
+#[cfg(all(feature = "std", windows))]
+mod getifaddrs_windows {
+    use super::{
+        HwAddrEntry, IfAddr, IfAddrsWarning, IfAddrsWarningKind, Ifv4Addr, Ifv6Addr, Interface,
+    };
+    use crate::luid::Luid;
+    use crate::options::Lookups;
+    use crate::windows::{self, Adapter};
+    use std::io;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+    /// Push the IP details of all the valid interfaces on this host onto `ret`, adding any
+    /// problems with individual entries to `warnings`.
+    pub fn get_if_addrs(
+        lookups: Lookups,
+        ret: &mut Vec<Interface>,
+        warnings: &mut Vec<IfAddrsWarning>,
+    ) -> io::Result<()> {
+        for ifaddr in windows::adapters()? {
+            push_adapter_if_addrs(&ifaddr, lookups, ret, warnings);
+        }
+        Ok(())
+    }
+
+    /// Return the IP details of the adapter with the given LUID.
+    pub fn get_adapter_if_addrs(luid: Luid) -> io::Result<Vec<Interface>> {
+        let mut ret = Vec::<Interface>::new();
+        push_adapter_if_addrs(
+            &windows::adapter(luid)?,
+            Lookups::DEFAULT,
+            &mut ret,
+            &mut Vec::new(),
+        );
+        Ok(ret)
+    }
+
+    /// Push an `Interface` for each unicast address of `ifaddr` onto `ret`, and any problems
+    /// with them onto `warnings`.
+    #[cfg_attr(feature = "lean", allow(unused_variables))]
+    fn push_adapter_if_addrs(
+        ifaddr: &Adapter,
+        lookups: Lookups,
+        ret: &mut Vec<Interface>,
+        warnings: &mut Vec<IfAddrsWarning>,
+    ) {
+        // The media state belongs to the adapter, so it is queried once for all its addresses.
+        #[cfg(not(feature = "lean"))]
+        let has_carrier = if lookups.status && !ifaddr.unicast_addresses.is_empty() {
+            match ifaddr.if_index {
+                0 => windows::media_connected(ifaddr.ipv6_if_index),
+                index => windows::media_connected(index),
+            }
+        } else {
+            None
+        };
+        for &addr in &ifaddr.unicast_addresses {
+            let addr = match addr {
+                IpAddr::V4(ipv4_addr) => {
+                    let mut item_netmask = Ipv4Addr::new(0, 0, 0, 0);
+                    let mut item_broadcast = None;
+
+                    // Search prefixes for a prefix matching addr
+                    'prefixloopv4: for prefix in &ifaddr.prefixes {
+                        match prefix.address {
+                            IpAddr::V4(ref a) => {
+                                let mut netmask: [u8; 4] = [0; 4];
+                                for (n, netmask_elt) in netmask
+                                    .iter_mut()
+                                    .enumerate()
+                                    .take((prefix.prefix_length as usize + 7) / 8)
+                                {
+                                    let x_byte = ipv4_addr.octets()[n];
+                                    let y_byte = a.octets()[n];
+                                    for m in 0..8 {
+                                        if (n * 8) + m > prefix.prefix_length as usize {
+                                            break;
+                                        }
+                                        let bit = 1 << m;
+                                        if (x_byte & bit) == (y_byte & bit) {
+                                            *netmask_elt |= bit;
+                                        } else {
+                                            continue 'prefixloopv4;
                                         }
                                     }
-                                    item_netmask = Ipv4Addr::new(
-                                        netmask[0], netmask[1], netmask[2], netmask[3],
-                                    );
-                                    let mut broadcast: [u8; 4] = ipv4_addr.octets();
-                                    for n in 0..4 {
-                                        broadcast[n] |= !netmask[n];
-                                    }
-                                    item_broadcast = Some(Ipv4Addr::new(
-                                        broadcast[0],
-                                        broadcast[1],
-                                        broadcast[2],
-                                        broadcast[3],
-                                    ));
-                                    break 'prefixloopv4;
                                 }
-                                _ => continue,
-                            };
-                        }
-                        IfAddr::V4(Ifv4Addr {
-                            ip: ipv4_addr,
-                            netmask: item_netmask,
-                            broadcast: item_broadcast,
-                        })
+                                item_netmask =
+                                    Ipv4Addr::new(netmask[0], netmask[1], netmask[2], netmask[3]);
+                                let mut broadcast: [u8; 4] = ipv4_addr.octets();
+                                for n in 0..4 {
+                                    broadcast[n] |= !netmask[n];
+                                }
+                                item_broadcast = Some(Ipv4Addr::new(
+                                    broadcast[0],
+                                    broadcast[1],
+                                    broadcast[2],
+                                    broadcast[3],
+                                ));
+                                break 'prefixloopv4;
+                            }
+                            _ => continue,
+                        };
                     }
-                    Some(IpAddr::V6(ipv6_addr)) => {
-                        let mut item_netmask = Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 0);
-                        // Search prefixes for a prefix matching addr
-                        'prefixloopv6: for prefix in ifaddr.prefixes() {
-                            let ipprefix = sockaddr::to_ipaddr(prefix.address.lp_socket_address);
-                            match ipprefix {
-                                Some(IpAddr::V6(ref a)) => {
-                                    // Iterate the bits in the prefix, if they all match this prefix
-                                    // is the right one, else try the next prefix
-                                    let mut netmask: [u16; 8] = [0; 8];
-                                    for (n, netmask_elt) in netmask
-                                        .iter_mut()
-                                        .enumerate()
-                                        .take((prefix.prefix_length as usize + 15) / 16)
-                                    {
-                                        let x_word = ipv6_addr.segments()[n];
-                                        let y_word = a.segments()[n];
-                                        for m in 0..16 {
-                                            if (n * 16) + m > prefix.prefix_length as usize {
-                                                break;
-                                            }
-                                            let bit = 1 << m;
-                                            if (x_word & bit) == (y_word & bit) {
-                                                *netmask_elt |= bit;
-                                            } else {
-                                                continue 'prefixloopv6;
-                                            }
+                    IfAddr::V4(Ifv4Addr {
+                        ip: ipv4_addr,
+                        netmask: item_netmask,
+                        broadcast: item_broadcast,
+                    })
+                }
+                IpAddr::V6(ipv6_addr) => {
+                    let mut item_netmask = Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 0);
+                    // Search prefixes for a prefix matching addr
+                    'prefixloopv6: for prefix in &ifaddr.prefixes {
+                        match prefix.address {
+                            IpAddr::V6(ref a) => {
+                                // Iterate the bits in the prefix, if they all match this prefix
+                                // is the right one, else try the next prefix
+                                let mut netmask: [u16; 8] = [0; 8];
+                                for (n, netmask_elt) in netmask
+                                    .iter_mut()
+                                    .enumerate()
+                                    .take((prefix.prefix_length as usize + 15) / 16)
+                                {
+                                    let x_word = ipv6_addr.segments()[n];
+                                    let y_word = a.segments()[n];
+                                    for m in 0..16 {
+                                        if (n * 16) + m > prefix.prefix_length as usize {
+                                            break;
+                                        }
+                                        let bit = 1 << m;
+                                        if (x_word & bit) == (y_word & bit) {
+                                            *netmask_elt |= bit;
+                                        } else {
+                                            continue 'prefixloopv6;
                                         }
                                     }
-                                    item_netmask = Ipv6Addr::new(
-                                        netmask[0], netmask[1], netmask[2], netmask[3], netmask[4],
-                                        netmask[5], netmask[6], netmask[7],
-                                    );
-                                    break 'prefixloopv6;
                                 }
-                                _ => continue,
-                            };
-                        }
-                        IfAddr::V6(Ifv6Addr {
-                            ip: ipv6_addr,
-                            netmask: item_netmask,
-                            broadcast: None,
-                        })
+                                item_netmask = Ipv6Addr::new(
+                                    netmask[0], netmask[1], netmask[2], netmask[3], netmask[4],
+                                    netmask[5], netmask[6], netmask[7],
+                                );
+                                break 'prefixloopv6;
+                            }
+                            _ => continue,
+                        };
                     }
-                };
+                    IfAddr::V6(Ifv6Addr {
+                        ip: ipv6_addr,
+                        netmask: item_netmask,
+                        broadcast: None,
+                    })
+                }
+            };
 
-                ret.push(Interface {
-                    name: ifaddr.name(),
-                    addr,
-                });
+            let index = match addr {
+                IfAddr::V4(_) => ifaddr.if_index,
+                IfAddr::V6(_) => ifaddr.ipv6_if_index,
+            };
+            // No prefix of the adapter covered the address.
+            if addr.netmask().is_unspecified() {
+                warnings.push(IfAddrsWarning::new(
+                    Some(&ifaddr.name),
+                    None,
+                    IfAddrsWarningKind::MissingNetmask,
+                ));
+            }
+            if index == 0 {
+                warnings.push(IfAddrsWarning::new(
+                    Some(&ifaddr.name),
+                    None,
+                    IfAddrsWarningKind::IndexLookupFailed,
+                ));
+            }
+            let mut iface = Interface::new(
+                ifaddr.name.clone(),
+                addr,
+                if index == 0 { None } else { Some(index) },
+            );
+            iface.virtual_device = Some(is_virtual(&ifaddr));
+            iface.vpn_device = Some(is_vpn_like(&ifaddr));
+            iface.wireless_device = Some(ifaddr.if_type == IF_TYPE_IEEE80211);
+            iface.cellular_device =
+                Some(ifaddr.if_type == IF_TYPE_WWANPP || ifaddr.if_type == IF_TYPE_WWANPP2);
+            iface.adapter_id = Some(ifaddr.name.clone());
+            #[cfg(not(feature = "lean"))]
+            {
+                iface.has_carrier = has_carrier;
             }
+            iface.ipv4_enabled = Some(ifaddr.ipv4_enabled());
+            iface.ipv6_enabled = Some(ifaddr.ipv6_enabled());
+            iface.dhcpv4_enabled = Some(ifaddr.dhcpv4_enabled());
+            ret.push(iface);
         }
+    }
 
-        Ok(ret)
+    const IF_TYPE_IEEE80211: u32 = 71;
+    const IF_TYPE_WWANPP: u32 = 243;
+    const IF_TYPE_WWANPP2: u32 = 244;
+
+    /// Classify an adapter as virtual from its IANA interface type and description.
+    fn is_virtual(ifaddr: &Adapter) -> bool {
+        const IF_TYPE_PROP_VIRTUAL: u32 = 53;
+        const IF_TYPE_SOFTWARE_LOOPBACK: u32 = 24;
+        const IF_TYPE_TUNNEL: u32 = 131;
+        const DESCRIPTIONS: [&str; 6] = [
+            "Virtual",
+            "Hyper-V",
+            "VMware",
+            "VirtualBox",
+            "TAP-Windows",
+            "Wintun",
+        ];
+        match ifaddr.if_type {
+            IF_TYPE_PROP_VIRTUAL | IF_TYPE_SOFTWARE_LOOPBACK | IF_TYPE_TUNNEL => true,
+            _ => DESCRIPTIONS.iter().any(|d| ifaddr.description.contains(d)),
+        }
+    }
+
+    /// Classify an adapter as a VPN tunnel from its IANA interface type and description.
+    fn is_vpn_like(ifaddr: &Adapter) -> bool {
+        const IF_TYPE_PPP: u32 = 23;
+        const DESCRIPTIONS: [&str; 5] = ["TAP-Windows", "WireGuard", "Wintun", "OpenVPN", "VPN"];
+        ifaddr.if_type == IF_TYPE_PPP || DESCRIPTIONS.iter().any(|d| ifaddr.description.contains(d))
     }
+
+    /// Return the name, index and 6-byte hardware address of every interface that has one.
+    pub fn get_hw_addrs() -> io::Result<Vec<HwAddrEntry>> {
+        Ok(windows::adapters_only()?
+            .into_iter()
+            .filter_map(|ifaddr| {
+                let index = if ifaddr.if_index == 0 {
+                    None
+                } else {
+                    Some(ifaddr.if_index)
+                };
+                Some((ifaddr.name, index, ifaddr.physical_address?))
+            })
+            .collect())
+    }
+}
+
+#[cfg(all(feature = "std", windows))]
+fn os_get_if_addrs_into(
+    lookups: Lookups,
+    ret: &mut Vec<Interface>,
+    warnings: &mut Vec<IfAddrsWarning>,
+) -> io::Result<()> {
+    getifaddrs_windows::get_if_addrs(lookups, ret, warnings)
+}
+
+/// Like `os_get_if_addrs_into`, returning the interfaces in a new vector.
+#[cfg(feature = "std")]
+fn os_get_if_addrs_with(
+    lookups: Lookups,
+    warnings: &mut Vec<IfAddrsWarning>,
+) -> io::Result<Vec<Interface>> {
+    let mut ret = Vec::new();
+    os_get_if_addrs_into(lookups, &mut ret, warnings)?;
+    Ok(ret)
 }
 
-#[cfg(windows)]
-/// Get address
+#[cfg(feature = "std")]
+fn os_get_if_addrs() -> io::Result<Vec<Interface>> {
+    os_get_if_addrs_with(Lookups::DEFAULT, &mut Vec::new())
+}
+
+#[cfg(all(feature = "std", windows))]
+fn os_get_hw_addrs() -> io::Result<Vec<HwAddrEntry>> {
+    getifaddrs_windows::get_hw_addrs()
+}
+
+/// Get a list of all the network interfaces on this machine along with their IP info.
+///
+/// If a custom source has been registered with [`set_source`], it is queried instead of the
+/// operating system. With the `env-override` feature, the results can further be replaced or
+/// augmented from a file named by the `IF_ADDRS_OVERRIDE` environment variable.
+#[cfg(feature = "std")]
 pub fn get_if_addrs() -> io::Result<Vec<Interface>> {
-    getifaddrs_windows::get_if_addrs()
+    source::get_if_addrs()
+}
+
+/// Like [`get_if_addrs`], but clears and refills `ifaces` instead of returning a new vector, so
+/// that callers polling in a loop keep its allocation.
+///
+/// On error, `ifaces` is left empty.
+#[cfg(feature = "std")]
+pub fn get_if_addrs_into(ifaces: &mut Vec<Interface>) -> io::Result<()> {
+    ifaces.clear();
+    let ret = source::get_if_addrs_into(Lookups::DEFAULT, ifaces);
+    if ret.is_err() {
+        ifaces.clear();
+    }
+    ret
+}
+
+/// Like [`get_if_addrs`], but gives up with an [`io::ErrorKind::TimedOut`] error if the
+/// enumeration takes longer than `timeout`, as it can on hosts with misbehaving drivers or VPN
+/// adapters.
+///
+/// The enumeration runs on a helper thread. When it times out, that thread is left to finish
+/// on its own and its result is discarded.
+#[cfg(feature = "std")]
+pub fn get_if_addrs_timeout(timeout: std::time::Duration) -> io::Result<Vec<Interface>> {
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::Builder::new()
+        .name("if-addrs".to_string())
+        .spawn(move || {
+            // The receiver is gone if we timed out, in which case nobody wants the result.
+            let _ = tx.send(source::get_if_addrs());
+        })?;
+    match rx.recv_timeout(timeout) {
+        Ok(result) => result,
+        Err(std::sync::mpsc::RecvTimeoutError::Timeout) => Err(io::Error::new(
+            io::ErrorKind::TimedOut,
+            "interface enumeration timed out",
+        )),
+        Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => {
+            Err(io::Error::other("interface enumeration thread panicked"))
+        }
+    }
+}
+
+/// Get the name and hardware (MAC) address of every interface that has a 6-byte one, without
+/// looking at IP addresses.
+///
+/// Unlike [`get_if_addrs`], this always asks the operating system.
+#[cfg(feature = "std")]
+pub fn get_hw_addrs() -> io::Result<Vec<(String, MacAddr)>> {
+    Ok(os_get_hw_addrs()?
+        .into_iter()
+        .map(|(name, _, hw_addr)| (name, MacAddr::from(hw_addr)))
+        .collect())
+}
+
+/// Get every IP address of the network interface with hardware (MAC) address `mac`.
+///
+/// The interface's current name and index are available on each returned entry. The result is
+/// empty if no interface has this hardware address, or if it has no IP address configured.
+#[cfg(feature = "std")]
+pub fn get_interface_by_mac<M: Into<MacAddr>>(mac: M) -> io::Result<Vec<Interface>> {
+    let mac = mac.into();
+    let links: Vec<_> = os_get_hw_addrs()?
+        .into_iter()
+        .filter(|&(_, _, hw_addr)| MacAddr::from(hw_addr) == mac)
+        .collect();
+    Ok(get_if_addrs()?
+        .into_iter()
+        .filter(|iface| {
+            links
+                .iter()
+                .any(|&(ref name, index, _)| match (iface.index, index) {
+                    (Some(a), Some(b)) => a == b,
+                    _ => iface.name == *name,
+                })
+        })
+        .collect())
+}
+
+/// Find the interface whose configured subnet contains `ip`, i.e. through which `ip` is directly
+/// reachable rather than via a gateway.
+///
+/// `ip` does not need to be a local address. When several subnets contain it, the one with the
+/// longest prefix wins. Addresses with an empty netmask are never considered on-link.
+#[cfg(feature = "std")]
+pub fn find_onlink_interface(ip: IpAddr) -> io::Result<Option<Interface>> {
+    let mut best: Option<(u8, Interface)> = None;
+    for iface in get_if_addrs()? {
+        let prefix_len = iface.addr.cidr().prefix_len();
+        if prefix_len == 0 || !iface.same_subnet(ip) {
+            continue;
+        }
+        match best {
+            Some((best_len, _)) if best_len >= prefix_len => {}
+            _ => best = Some((prefix_len, iface)),
+        }
+    }
+    Ok(best.map(|(_, iface)| iface))
+}
+
+/// Get the deduplicated list of subnets configured on this host, as network addresses such as
+/// `192.168.1.0/24` or `fd00::/64`.
+#[cfg(feature = "std")]
+pub fn local_prefixes() -> io::Result<Vec<Cidr>> {
+    let mut ret = Vec::new();
+    for iface in get_if_addrs()? {
+        let prefix = iface.addr.cidr().network();
+        if !ret.contains(&prefix) {
+            ret.push(prefix);
+        }
+    }
+    Ok(ret)
+}
+
+/// Check whether an up interface has a globally routable IPv4 address, i.e. one outside the
+/// private, shared (carrier-grade NAT), loopback, link-local, documentation and other
+/// special-purpose ranges.
+#[cfg(feature = "std")]
+pub fn has_global_ipv4() -> io::Result<bool> {
+    Ok(get_if_addrs()?
+        .iter()
+        .any(|iface| iface.ip().is_ipv4() && iface.is_up() && is_global(iface.ip())))
+}
+
+/// Check whether an up interface has a globally routable IPv6 address, i.e. a global unicast
+/// one (`2000::/3`) outside the documentation range. Unique local (`fc00::/7`) and link-local
+/// addresses do not count.
+#[cfg(feature = "std")]
+pub fn has_global_ipv6() -> io::Result<bool> {
+    Ok(get_if_addrs()?
+        .iter()
+        .any(|iface| !iface.ip().is_ipv4() && iface.is_up() && is_global(iface.ip())))
+}
+
+/// Check whether `ip` is link-local: IPv4 `169.254.0.0/16` or IPv6 `fe80::/10`.
+fn is_link_local(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => ip.is_link_local(),
+        IpAddr::V6(ip) => ip.segments()[0] & 0xffc0 == 0xfe80,
+    }
+}
+
+#[cfg(feature = "std")]
+fn is_global(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, c, _] = ip.octets();
+            !(a == 0
+                || ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                || a >= 240
+                // Shared address space, RFC 6598.
+                || (a == 100 && b & 0xc0 == 64)
+                // IETF protocol assignments, RFC 6890.
+                || (a == 192 && b == 0 && c == 0)
+                // Benchmarking, RFC 2544.
+                || (a == 198 && b & 0xfe == 18))
+        }
+        IpAddr::V6(ip) => {
+            let segments = ip.segments();
+            segments[0] & 0xe000 == 0x2000 && !(segments[0] == 0x2001 && segments[1] == 0xdb8)
+        }
+    }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std"))]
 mod tests {
-    use super::{get_if_addrs, Interface};
+    use super::{get_if_addrs, IfAddr, Ifv4Addr, Ifv6Addr, Interface, TransitionTechnology};
     use std::io::Read;
-    use std::net::{IpAddr, Ipv4Addr};
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
     use std::process::{Command, Stdio};
     use std::str::FromStr;
     use std::thread;
     use std::time::Duration;
 
     fn list_system_interfaces(cmd: &str, arg: &str) -> String {
-        let start_cmd = if arg == "" {
+        let start_cmd = if arg.is_empty() {
             Command::new(cmd).stdout(Stdio::piped()).spawn()
         } else {
             Command::new(cmd).arg(arg).stdout(Stdio::piped()).spawn()
         };
         let mut process = match start_cmd {
             Err(why) => {
-                println!("couldn't start cmd {} : {}", cmd, why.to_string());
+                println!("couldn't start cmd {} : {}", cmd, why);
                 return "".to_string();
             }
             Ok(process) => process,
         };
         thread::sleep(Duration::from_millis(1000));
         let _ = process.kill();
-        let result: Vec<u8> = process
-            .stdout
-            .unwrap()
-            .bytes()
-            .map(|x| x.unwrap())
-            .collect();
+        let mut result = Vec::new();
+        process.stdout.unwrap().read_to_end(&mut result).unwrap();
         String::from_utf8(result).unwrap()
     }
 
     #[cfg(windows)]
     fn list_system_addrs() -> Vec<IpAddr> {
-        use std::net::Ipv6Addr;
         list_system_interfaces("ipconfig", "")
             .lines()
             .filter_map(|line| {
@@ -363,7 +1447,7 @@ mod tests {
             .collect()
     }
 
-    #[cfg(any(target_os = "linux", target_os = "android", target_os = "nacl"))]
+    #[cfg(any(target_os = "linux", target_os = "android"))]
     fn list_system_addrs() -> Vec<IpAddr> {
         list_system_interfaces("ip", "addr")
             .lines()
@@ -425,4 +1509,140 @@ mod tests {
             assert!(listed);
         }
     }
+
+    #[test]
+    fn test_same_subnet() {
+        let v4 = IfAddr::V4(Ifv4Addr {
+            ip: Ipv4Addr::new(192, 168, 1, 10),
+            netmask: Ipv4Addr::new(255, 255, 255, 0),
+            broadcast: None,
+        });
+        assert!(v4.same_subnet(IpAddr::V4(Ipv4Addr::new(192, 168, 1, 200))));
+        assert!(!v4.same_subnet(IpAddr::V4(Ipv4Addr::new(192, 168, 2, 1))));
+        assert!(!v4.same_subnet(IpAddr::V6(Ipv6Addr::LOCALHOST)));
+
+        let v6 = IfAddr::V6(Ifv6Addr {
+            ip: "2001:db8::1".parse().unwrap(),
+            netmask: "ffff:ffff:ffff:ffff::".parse().unwrap(),
+            broadcast: None,
+        });
+        assert!(v6.same_subnet("2001:db8::abcd".parse().unwrap()));
+        assert!(!v6.same_subnet("2001:db8:0:1::1".parse().unwrap()));
+        assert!(!v6.same_subnet("fe80::1".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_is_global() {
+        for ip in &[
+            "8.8.8.8",
+            "100.128.0.1",
+            "2606:4700::1111",
+            "2002:c000:204::1",
+        ] {
+            assert!(super::is_global(ip.parse().unwrap()), "{}", ip);
+        }
+        for ip in &[
+            "10.0.0.1",
+            "172.16.0.1",
+            "192.168.1.1",
+            "100.64.0.1",
+            "169.254.1.1",
+            "192.0.2.1",
+            "198.18.0.1",
+            "255.255.255.255",
+            "fd00::1",
+            "fe80::1",
+            "2001:db8::1",
+            "::1",
+        ] {
+            assert!(!super::is_global(ip.parse().unwrap()), "{}", ip);
+        }
+    }
+
+    #[test]
+    fn test_transition_technology() {
+        let v6 = |ip: &str| Ifv6Addr::new(ip.parse().unwrap(), Ipv6Addr::UNSPECIFIED, None);
+        assert_eq!(
+            Some(TransitionTechnology::SixToFour),
+            v6("2002:c000:204::1").transition_technology()
+        );
+        assert_eq!(
+            Some(TransitionTechnology::Teredo),
+            v6("2001:0:4136:e378:8000:63bf:3fff:fdd2").transition_technology()
+        );
+        assert_eq!(
+            Some(TransitionTechnology::Isatap),
+            v6("fe80::5efe:192.0.2.1").transition_technology()
+        );
+        assert_eq!(
+            Some(TransitionTechnology::Isatap),
+            v6("2001:db8::200:5efe:192.0.2.1").transition_technology()
+        );
+        assert_eq!(None, v6("2001:db8::1").transition_technology());
+    }
+
+    #[test]
+    fn test_is_up() {
+        let mut iface = Interface::new(
+            "eth0".to_string(),
+            IfAddr::V4(Ifv4Addr::new(
+                Ipv4Addr::new(192, 0, 2, 1),
+                Ipv4Addr::new(255, 255, 255, 0),
+                None,
+            )),
+            Some(2),
+        );
+        // Without flags, only a known missing carrier counts as down.
+        assert!(iface.is_up());
+        iface.has_carrier = Some(false);
+        assert!(!iface.is_up());
+        iface.has_carrier = Some(true);
+        assert!(iface.is_up());
+
+        iface.flags = Some(super::IFF_UP | super::IFF_RUNNING);
+        assert!(iface.is_up());
+        iface.flags = Some(super::IFF_UP);
+        assert!(!iface.is_up());
+        iface.flags = Some(super::IFF_RUNNING);
+        assert!(!iface.is_up());
+    }
+
+    #[test]
+    fn test_is_ipv4_autoconfigured() {
+        let iface = |ip: Ipv4Addr, dhcpv4_enabled: Option<bool>| {
+            let mut iface = Interface::new(
+                "Ethernet".to_string(),
+                IfAddr::V4(Ifv4Addr::new(ip, Ipv4Addr::new(255, 255, 0, 0), None)),
+                Some(4),
+            );
+            iface.dhcpv4_enabled = dhcpv4_enabled;
+            iface
+        };
+        let apipa = Ipv4Addr::new(169, 254, 12, 34);
+        assert!(iface(apipa, Some(true)).is_ipv4_autoconfigured());
+        assert!(!iface(apipa, Some(false)).is_ipv4_autoconfigured());
+        assert!(!iface(apipa, None).is_ipv4_autoconfigured());
+        assert!(!iface(Ipv4Addr::new(192, 168, 1, 10), Some(true)).is_ipv4_autoconfigured());
+    }
+
+    #[test]
+    fn test_is_apple_internal() {
+        let iface = |name: &str| {
+            Interface::new(
+                name.to_string(),
+                IfAddr::V4(Ifv4Addr {
+                    ip: Ipv4Addr::new(10, 0, 0, 1),
+                    netmask: Ipv4Addr::new(255, 0, 0, 0),
+                    broadcast: None,
+                }),
+                None,
+            )
+        };
+        for name in &["awdl0", "llw0", "utun3", "ipsec0", "pdp_ip0"] {
+            assert!(iface(name).is_apple_internal(), "{}", name);
+        }
+        for name in &["en0", "lo0", "bridge100", "utunnel"] {
+            assert!(!iface(name).is_apple_internal(), "{}", name);
+        }
+    }
 }
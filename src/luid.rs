@@ -0,0 +1,164 @@
+// Copyright 2018 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Conversions between the identifiers Windows uses for an interface: the locally unique
+//! identifier (LUID) most IP Helper APIs take, the interface index and the alias shown to users,
+//! e.g. `Ethernet` or `Wi-Fi`, and the adapter GUID that [`Interface::name`] holds.
+//!
+//! Services watching a known adapter can use [`get_adapter_addrs()`] to look up its addresses
+//! without enumerating every adapter on the host.
+
+use crate::Interface;
+use std::ffi::{OsStr, OsString};
+use std::io;
+use std::os::windows::ffi::{OsStrExt, OsStringExt};
+use winapi::shared::guiddef::GUID;
+use winapi::shared::ifdef::{IF_MAX_STRING_SIZE, NET_LUID};
+use winapi::shared::netioapi::{
+    ConvertInterfaceAliasToLuid, ConvertInterfaceGuidToLuid, ConvertInterfaceIndexToLuid,
+    ConvertInterfaceLuidToAlias, ConvertInterfaceLuidToGuid, ConvertInterfaceLuidToIndex,
+};
+use winapi::shared::winerror::NO_ERROR;
+
+/// The locally unique identifier of an interface, the `NET_LUID` of the IP Helper API.
+///
+/// Unlike the index, a LUID stays the same across reboots as long as the interface exists.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
+pub struct Luid(pub u64);
+
+impl Luid {
+    /// The IANA interface type encoded in the LUID, e.g. `6` for Ethernet.
+    pub fn if_type(self) -> u32 {
+        (self.0 >> 48) as u32
+    }
+
+    fn to_net_luid(self) -> NET_LUID {
+        NET_LUID { Value: self.0 }
+    }
+}
+
+fn check(ret: u32) -> io::Result<()> {
+    if ret == NO_ERROR {
+        Ok(())
+    } else {
+        Err(io::Error::from_raw_os_error(ret as i32))
+    }
+}
+
+/// Get the index of the interface with the given LUID.
+#[allow(unsafe_code)]
+pub fn luid_to_index(luid: Luid) -> io::Result<u32> {
+    let mut index = 0;
+    check(unsafe { ConvertInterfaceLuidToIndex(&luid.to_net_luid(), &mut index) })?;
+    Ok(index)
+}
+
+/// Get the LUID of the interface with the given index.
+#[allow(unsafe_code)]
+pub fn index_to_luid(index: u32) -> io::Result<Luid> {
+    let mut luid = Luid(0).to_net_luid();
+    check(unsafe { ConvertInterfaceIndexToLuid(index, &mut luid) })?;
+    Ok(Luid(luid.Value))
+}
+
+/// Get the alias of the interface with the given LUID.
+#[allow(unsafe_code)]
+pub fn luid_to_alias(luid: Luid) -> io::Result<String> {
+    let mut alias = [0u16; IF_MAX_STRING_SIZE + 1];
+    check(unsafe {
+        ConvertInterfaceLuidToAlias(&luid.to_net_luid(), alias.as_mut_ptr(), alias.len())
+    })?;
+    let len = alias.iter().position(|&c| c == 0).unwrap_or(alias.len());
+    Ok(OsString::from_wide(&alias[..len])
+        .to_string_lossy()
+        .into_owned())
+}
+
+/// Get the LUID of the interface with the given alias.
+#[allow(unsafe_code)]
+pub fn alias_to_luid(alias: &str) -> io::Result<Luid> {
+    if alias.contains('\0') {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "interface alias contains a NUL byte",
+        ));
+    }
+    let alias: Vec<u16> = OsStr::new(alias).encode_wide().chain(Some(0)).collect();
+    let mut luid = Luid(0).to_net_luid();
+    check(unsafe { ConvertInterfaceAliasToLuid(alias.as_ptr(), &mut luid) })?;
+    Ok(Luid(luid.Value))
+}
+
+/// Get the LUID of the adapter with the given GUID, as in [`Interface::name`], e.g.
+/// `{4D36E972-E325-11CE-BFC1-08002BE10318}`.
+#[allow(unsafe_code)]
+pub fn guid_to_luid(guid: &str) -> io::Result<Luid> {
+    let guid = parse_guid(guid)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid adapter GUID"))?;
+    let mut luid = Luid(0).to_net_luid();
+    check(unsafe { ConvertInterfaceGuidToLuid(&guid, &mut luid) })?;
+    Ok(Luid(luid.Value))
+}
+
+/// Get the GUID of the adapter with the given LUID, formatted like [`Interface::name`].
+#[allow(unsafe_code)]
+pub fn luid_to_guid(luid: Luid) -> io::Result<String> {
+    let mut guid = GUID {
+        Data1: 0,
+        Data2: 0,
+        Data3: 0,
+        Data4: [0; 8],
+    };
+    check(unsafe { ConvertInterfaceLuidToGuid(&luid.to_net_luid(), &mut guid) })?;
+    Ok(format_guid(&guid))
+}
+
+/// Get the addresses of the adapter with the given GUID like
+/// [`get_if_addrs`](crate::get_if_addrs) would, without enumerating the other adapters.
+///
+/// This reads the adapter's `MIB_IF_ROW2` and the unicast address table instead of
+/// `GetAdaptersAddresses`, so `dhcpv4_enabled` is only set when an IPv4 address came from DHCP.
+pub fn get_adapter_addrs(guid: &str) -> io::Result<Vec<Interface>> {
+    get_adapter_addrs_by_luid(guid_to_luid(guid)?)
+}
+
+/// Get the addresses of the adapter with the given LUID, like [`get_adapter_addrs()`].
+pub fn get_adapter_addrs_by_luid(luid: Luid) -> io::Result<Vec<Interface>> {
+    crate::getifaddrs_windows::get_adapter_if_addrs(luid)
+}
+
+/// Format `guid` in braces and upper case, the way `GetAdaptersAddresses` names adapters.
+pub(crate) fn format_guid(guid: &GUID) -> String {
+    let d = &guid.Data4;
+    format!(
+        "{{{:08X}-{:04X}-{:04X}-{:02X}{:02X}-{:02X}{:02X}{:02X}{:02X}{:02X}{:02X}}}",
+        guid.Data1, guid.Data2, guid.Data3, d[0], d[1], d[2], d[3], d[4], d[5], d[6], d[7]
+    )
+}
+
+/// Parse a GUID in the form `format_guid` produces, with the braces optional.
+fn parse_guid(s: &str) -> Option<GUID> {
+    let s = s.trim_start_matches('{').trim_end_matches('}');
+    let parts: Vec<&str> = s.split('-').collect();
+    let lens: Vec<usize> = parts.iter().map(|part| part.len()).collect();
+    if lens != [8, 4, 4, 4, 12] || !s.chars().all(|c| c == '-' || c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let tail = format!("{}{}", parts[3], parts[4]);
+    let mut data4 = [0; 8];
+    for (i, byte) in data4.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&tail[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(GUID {
+        Data1: u32::from_str_radix(parts[0], 16).ok()?,
+        Data2: u16::from_str_radix(parts[1], 16).ok()?,
+        Data3: u16::from_str_radix(parts[2], 16).ok()?,
+        Data4: data4,
+    })
+}
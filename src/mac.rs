@@ -0,0 +1,227 @@
+// Copyright 2018 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use core::fmt;
+#[cfg(not(feature = "std"))]
+use core::net::Ipv6Addr;
+use core::str::FromStr;
+#[cfg(feature = "std")]
+use std::net::Ipv6Addr;
+
+/// A hardware (MAC) address, displayed as `aa:bb:cc:dd:ee:ff`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
+pub enum MacAddr {
+    /// A 6-byte EUI-48 address, as used by Ethernet and WiFi.
+    Eui48([u8; 6]),
+    /// An 8-byte EUI-64 address, as used by IEEE 802.15.4 and FireWire.
+    Eui64([u8; 8]),
+}
+
+impl MacAddr {
+    /// Create a 6-byte `MacAddr` from its bytes.
+    pub const fn new(octets: [u8; 6]) -> Self {
+        MacAddr::Eui48(octets)
+    }
+
+    /// Create an 8-byte `MacAddr` from its bytes.
+    pub const fn new_eui64(octets: [u8; 8]) -> Self {
+        MacAddr::Eui64(octets)
+    }
+
+    /// The bytes of the address.
+    pub fn octets(&self) -> &[u8] {
+        match *self {
+            MacAddr::Eui48(ref octets) => octets,
+            MacAddr::Eui64(ref octets) => octets,
+        }
+    }
+
+    /// Derive the modified EUI-64 interface identifier of the address, the lower 64 bits of the
+    /// IPv6 addresses SLAAC forms from it (RFC 4291, appendix A).
+    ///
+    /// An EUI-48 address is expanded by inserting `ff:fe` in the middle. In both cases the
+    /// universal/local bit is inverted.
+    pub fn interface_identifier(&self) -> [u8; 8] {
+        let mut id = match *self {
+            MacAddr::Eui48([a, b, c, d, e, f]) => [a, b, c, 0xff, 0xfe, d, e, f],
+            MacAddr::Eui64(octets) => octets,
+        };
+        id[0] ^= 0x02;
+        id
+    }
+
+    /// Check whether the interface identifier of `ip` was derived from this address, as SLAAC
+    /// does without privacy extensions. Temporary and stable-privacy (RFC 7217) addresses are
+    /// not.
+    pub fn matches_ipv6(&self, ip: Ipv6Addr) -> bool {
+        ip.octets()[8..] == self.interface_identifier()
+    }
+
+    /// Recover the EUI-48 address the interface identifier of `ip` was derived from, if it has
+    /// the `ff:fe` marker of one.
+    pub fn from_ipv6(ip: Ipv6Addr) -> Option<MacAddr> {
+        match ip.octets()[8..] {
+            [a, b, c, 0xff, 0xfe, d, e, f] => Some(MacAddr::Eui48([a ^ 0x02, b, c, d, e, f])),
+            _ => None,
+        }
+    }
+
+    /// Display the address with hyphens and in upper case, e.g. `AA-BB-CC-DD-EE-FF`, the way
+    /// Windows shows it.
+    pub fn hyphenated(&self) -> Hyphenated<'_> {
+        Hyphenated(self)
+    }
+
+    /// Get the manufacturer of the interface from the OUI in the first three bytes, e.g.
+    /// `Intel` or `VMware`.
+    ///
+    /// `None` for multicast and locally administered addresses, which carry no OUI, and for
+    /// vendors missing from the compact table this crate embeds.
+    #[cfg(feature = "oui")]
+    pub fn vendor(&self) -> Option<&'static str> {
+        let octets = self.octets();
+        // The I/G and U/L bits.
+        if octets[0] & 0x03 != 0 {
+            return None;
+        }
+        crate::oui::vendor([octets[0], octets[1], octets[2]])
+    }
+}
+
+impl From<[u8; 6]> for MacAddr {
+    fn from(octets: [u8; 6]) -> Self {
+        MacAddr::Eui48(octets)
+    }
+}
+
+impl From<[u8; 8]> for MacAddr {
+    fn from(octets: [u8; 8]) -> Self {
+        MacAddr::Eui64(octets)
+    }
+}
+
+impl fmt::Display for MacAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, octet) in self.octets().iter().enumerate() {
+            if i > 0 {
+                f.write_str(":")?;
+            }
+            write!(f, "{:02x}", octet)?;
+        }
+        Ok(())
+    }
+}
+
+/// Displays a [`MacAddr`] as `AA-BB-CC-DD-EE-FF`. Returned by [`MacAddr::hyphenated`].
+#[derive(Debug, Clone, Copy)]
+pub struct Hyphenated<'a>(&'a MacAddr);
+
+impl fmt::Display for Hyphenated<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, octet) in self.0.octets().iter().enumerate() {
+            if i > 0 {
+                f.write_str("-")?;
+            }
+            write!(f, "{:02X}", octet)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for MacAddr {
+    type Err = ParseMacAddrError;
+
+    /// Parse 6 or 8 two-digit hex bytes separated by either colons or hyphens, e.g.
+    /// `aa:bb:cc:dd:ee:ff` or `AA-BB-CC-DD-EE-FF`.
+    fn from_str(s: &str) -> Result<MacAddr, ParseMacAddrError> {
+        let separator = if s.contains(':') { ':' } else { '-' };
+        let mut octets = [0; 8];
+        let mut len = 0;
+        for part in s.split(separator) {
+            if len == octets.len()
+                || part.len() != 2
+                || !part.bytes().all(|b| b.is_ascii_hexdigit())
+            {
+                return Err(ParseMacAddrError(()));
+            }
+            octets[len] = u8::from_str_radix(part, 16).map_err(|_| ParseMacAddrError(()))?;
+            len += 1;
+        }
+        match len {
+            6 => Ok(MacAddr::Eui48([
+                octets[0], octets[1], octets[2], octets[3], octets[4], octets[5],
+            ])),
+            8 => Ok(MacAddr::Eui64(octets)),
+            _ => Err(ParseMacAddrError(())),
+        }
+    }
+}
+
+/// The error returned when parsing a [`MacAddr`] fails.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ParseMacAddrError(());
+
+impl fmt::Display for ParseMacAddrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid MAC address")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseMacAddrError {}
+
+#[cfg(test)]
+mod tests {
+    use super::MacAddr;
+    use alloc::string::ToString;
+
+    #[test]
+    fn test_parse_and_display() {
+        let mac: MacAddr = "00:1B:21:0a:0b:0c".parse().unwrap();
+        assert_eq!(mac, MacAddr::new([0x00, 0x1b, 0x21, 0x0a, 0x0b, 0x0c]));
+        assert_eq!(mac.to_string(), "00:1b:21:0a:0b:0c");
+        assert_eq!(mac.hyphenated().to_string(), "00-1B-21-0A-0B-0C");
+        assert_eq!("00-1B-21-0A-0B-0C".parse(), Ok(mac));
+
+        let eui64: MacAddr = "02:12:4b:00:01:02:03:04".parse().unwrap();
+        assert_eq!(eui64.octets().len(), 8);
+        assert_eq!(eui64.to_string(), "02:12:4b:00:01:02:03:04");
+
+        for bad in &[
+            "",
+            "00:1b:21:0a:0b",
+            "00:1b:21:0a:0b:0c:0d",
+            "00:1b:21-0a:0b:0c",
+            "00:1b:21:0a:0b:c",
+            "00:1b:21:0a:0b:+c",
+            "00:1b:21:0a:0b:0c:0d:0e:0f",
+        ] {
+            assert!(bad.parse::<MacAddr>().is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_interface_identifier() {
+        let mac = MacAddr::new([0x00, 0x1b, 0x21, 0x0a, 0x0b, 0x0c]);
+        assert_eq!(
+            mac.interface_identifier(),
+            [0x02, 0x1b, 0x21, 0xff, 0xfe, 0x0a, 0x0b, 0x0c]
+        );
+        let slaac = "2001:db8::21b:21ff:fe0a:b0c".parse().unwrap();
+        assert!(mac.matches_ipv6(slaac));
+        assert_eq!(MacAddr::from_ipv6(slaac), Some(mac));
+
+        let stable_privacy = "2001:db8::8c4e:6a1d:93f2:7b05".parse().unwrap();
+        assert!(!mac.matches_ipv6(stable_privacy));
+        assert_eq!(MacAddr::from_ipv6(stable_privacy), None);
+
+        let eui64 = MacAddr::new_eui64([0x00, 0x12, 0x4b, 0x00, 0x01, 0x02, 0x03, 0x04]);
+        assert!(eui64.matches_ipv6("fe80::212:4b00:102:304".parse().unwrap()));
+    }
+}
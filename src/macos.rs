@@ -0,0 +1,106 @@
+// Copyright 2018 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use libc::{c_char, c_long, c_void};
+use std::collections::HashMap;
+use std::ffi::CStr;
+
+type CFIndex = c_long;
+type CFTypeRef = *const c_void;
+type CFArrayRef = *const c_void;
+type CFStringRef = *const c_void;
+type SCNetworkInterfaceRef = *const c_void;
+
+const K_CF_STRING_ENCODING_UTF8: u32 = 0x0800_0100;
+
+/// SystemConfiguration details of a BSD interface.
+pub struct ScInterface {
+    pub interface_type: Option<String>,
+    pub display_name: Option<String>,
+    pub hardware_address: Option<String>,
+}
+
+/// Map every BSD name known to SystemConfiguration to its interface type, localized display
+/// name and hardware address.
+#[allow(unsafe_code)]
+pub fn sc_interfaces() -> HashMap<String, ScInterface> {
+    let mut ret = HashMap::new();
+    let all = unsafe { SCNetworkInterfaceCopyAll() };
+    if all.is_null() {
+        return ret;
+    }
+
+    for i in 0..unsafe { CFArrayGetCount(all) } {
+        let interface = unsafe { CFArrayGetValueAtIndex(all, i) };
+        let name = match to_string(unsafe { SCNetworkInterfaceGetBSDName(interface) }) {
+            Some(name) => name,
+            None => continue,
+        };
+        ret.insert(
+            name,
+            ScInterface {
+                interface_type: to_string(unsafe { SCNetworkInterfaceGetInterfaceType(interface) }),
+                display_name: to_string(unsafe {
+                    SCNetworkInterfaceGetLocalizedDisplayName(interface)
+                }),
+                hardware_address: to_string(unsafe {
+                    SCNetworkInterfaceGetHardwareAddressString(interface)
+                }),
+            },
+        );
+    }
+
+    unsafe { CFRelease(all) };
+    ret
+}
+
+#[allow(unsafe_code)]
+fn to_string(string: CFStringRef) -> Option<String> {
+    if string.is_null() {
+        return None;
+    }
+    let mut buf = [0 as c_char; 256];
+    if !unsafe {
+        CFStringGetCString(
+            string,
+            buf.as_mut_ptr(),
+            buf.len() as CFIndex,
+            K_CF_STRING_ENCODING_UTF8,
+        )
+    } {
+        return None;
+    }
+    Some(
+        unsafe { CStr::from_ptr(buf.as_ptr()) }
+            .to_string_lossy()
+            .into_owned(),
+    )
+}
+
+#[link(name = "CoreFoundation", kind = "framework")]
+extern "C" {
+    fn CFArrayGetCount(array: CFArrayRef) -> CFIndex;
+    fn CFArrayGetValueAtIndex(array: CFArrayRef, index: CFIndex) -> *const c_void;
+    fn CFStringGetCString(
+        string: CFStringRef,
+        buffer: *mut c_char,
+        buffer_size: CFIndex,
+        encoding: u32,
+    ) -> bool;
+    fn CFRelease(cf: CFTypeRef);
+}
+
+#[link(name = "SystemConfiguration", kind = "framework")]
+extern "C" {
+    fn SCNetworkInterfaceCopyAll() -> CFArrayRef;
+    fn SCNetworkInterfaceGetBSDName(interface: SCNetworkInterfaceRef) -> CFStringRef;
+    fn SCNetworkInterfaceGetInterfaceType(interface: SCNetworkInterfaceRef) -> CFStringRef;
+    fn SCNetworkInterfaceGetLocalizedDisplayName(interface: SCNetworkInterfaceRef) -> CFStringRef;
+    fn SCNetworkInterfaceGetHardwareAddressString(interface: SCNetworkInterfaceRef) -> CFStringRef;
+}
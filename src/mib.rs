@@ -0,0 +1,145 @@
+// Copyright 2018 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Per-interface MIB data on Windows from `GetIfTable2` and `GetIpInterfaceTable`, covering
+//! what `GetAdaptersAddresses` does not report: link speeds, per-family MTUs, status and
+//! traffic counters.
+
+use crate::Interface;
+use std::{io, ptr, slice};
+use winapi::shared::netioapi::{
+    FreeMibTable, GetIfTable2, GetIpInterfaceTable, PMIB_IF_TABLE2, PMIB_IPINTERFACE_TABLE,
+};
+use winapi::shared::winerror::NO_ERROR;
+use winapi::shared::ws2def::{AF_INET, AF_INET6, AF_UNSPEC};
+
+/// The MIB row of an interface.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct IfMib {
+    /// The index of the interface.
+    pub index: u32,
+    /// The IANA interface type, e.g. `6` for Ethernet or `71` for IEEE 802.11.
+    pub if_type: u32,
+    /// The MTU of the link layer.
+    pub mtu: u32,
+    /// The IPv4 MTU, if IPv4 is enabled on the interface.
+    pub ipv4_mtu: Option<u32>,
+    /// The IPv6 MTU, if IPv6 is enabled on the interface.
+    pub ipv6_mtu: Option<u32>,
+    /// The transmit speed in bits per second.
+    pub transmit_link_speed: u64,
+    /// The receive speed in bits per second.
+    pub receive_link_speed: u64,
+    /// Whether the interface is administratively enabled.
+    pub admin_up: bool,
+    /// Whether the interface is operationally up, i.e. can pass packets.
+    pub oper_up: bool,
+    /// The raw `NET_IF_ADMIN_STATUS` that `admin_up` is derived from.
+    pub admin_status: u32,
+    /// The raw `IF_OPER_STATUS` that `oper_up` is derived from, e.g. `5` for
+    /// `IfOperStatusNotPresent` or `7` for `IfOperStatusLowerLayerDown`.
+    pub oper_status: u32,
+    /// The raw `NET_IF_MEDIA_CONNECT_STATE` that [`Interface::has_carrier`] is derived from.
+    pub media_connect_state: u32,
+    /// Octets received.
+    pub in_octets: u64,
+    /// Octets sent.
+    pub out_octets: u64,
+    /// Unicast packets received.
+    pub in_unicast_packets: u64,
+    /// Unicast packets sent.
+    pub out_unicast_packets: u64,
+    /// Received packets discarded although they had no errors.
+    pub in_discards: u64,
+    /// Packets to send discarded although they had no errors.
+    pub out_discards: u64,
+    /// Received packets with errors.
+    pub in_errors: u64,
+    /// Packets that could not be sent because of errors.
+    pub out_errors: u64,
+}
+
+/// Get the MIB row of every interface on this host, including those without addresses.
+#[allow(unsafe_code)]
+pub fn get_if_mibs() -> io::Result<Vec<IfMib>> {
+    const NET_IF_ADMIN_STATUS_UP: u32 = 1;
+    const IF_OPER_STATUS_UP: u32 = 1;
+
+    let mut table: PMIB_IF_TABLE2 = ptr::null_mut();
+    let ret = unsafe { GetIfTable2(&mut table) };
+    if ret != NO_ERROR {
+        return Err(io::Error::from_raw_os_error(ret as i32));
+    }
+    let rows =
+        unsafe { slice::from_raw_parts((*table).Table.as_ptr(), (*table).NumEntries as usize) };
+    let mut mibs: Vec<IfMib> = rows
+        .iter()
+        .map(|row| IfMib {
+            index: row.InterfaceIndex,
+            if_type: row.Type,
+            mtu: row.Mtu,
+            ipv4_mtu: None,
+            ipv6_mtu: None,
+            transmit_link_speed: row.TransmitLinkSpeed,
+            receive_link_speed: row.ReceiveLinkSpeed,
+            admin_up: row.AdminStatus == NET_IF_ADMIN_STATUS_UP,
+            oper_up: row.OperStatus == IF_OPER_STATUS_UP,
+            admin_status: row.AdminStatus,
+            oper_status: row.OperStatus,
+            media_connect_state: row.MediaConnectState,
+            in_octets: row.InOctets,
+            out_octets: row.OutOctets,
+            in_unicast_packets: row.InUcastPkts,
+            out_unicast_packets: row.OutUcastPkts,
+            in_discards: row.InDiscards,
+            out_discards: row.OutDiscards,
+            in_errors: row.InErrors,
+            out_errors: row.OutErrors,
+        })
+        .collect();
+    unsafe { FreeMibTable(table as *mut _) };
+
+    let mut table: PMIB_IPINTERFACE_TABLE = ptr::null_mut();
+    let ret = unsafe { GetIpInterfaceTable(AF_UNSPEC as u16, &mut table) };
+    if ret != NO_ERROR {
+        return Err(io::Error::from_raw_os_error(ret as i32));
+    }
+    let rows =
+        unsafe { slice::from_raw_parts((*table).Table.as_ptr(), (*table).NumEntries as usize) };
+    for row in rows {
+        let mib = match mibs.iter_mut().find(|mib| mib.index == row.InterfaceIndex) {
+            Some(mib) => mib,
+            None => continue,
+        };
+        match i32::from(row.Family) {
+            AF_INET => mib.ipv4_mtu = Some(row.NlMtu),
+            AF_INET6 => mib.ipv6_mtu = Some(row.NlMtu),
+            _ => {}
+        }
+    }
+    unsafe { FreeMibTable(table as *mut _) };
+
+    Ok(mibs)
+}
+
+/// Get the interfaces like [`get_if_addrs`](crate::get_if_addrs), each joined with the MIB
+/// row of the same index.
+pub fn get_if_addrs_with_mib() -> io::Result<Vec<(Interface, Option<IfMib>)>> {
+    let mibs = get_if_mibs()?;
+    Ok(crate::get_if_addrs()?
+        .into_iter()
+        .map(|iface| {
+            let mib = mibs
+                .iter()
+                .find(|mib| Some(mib.index) == iface.index)
+                .cloned();
+            (iface, mib)
+        })
+        .collect())
+}
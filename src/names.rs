@@ -0,0 +1,140 @@
+// Copyright 2018 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Interface names and checks on them that skip building per-address details.
+
+use std::io;
+
+/// Check whether an interface named `name` exists, regardless of whether it has addresses.
+///
+/// This asks the operating system directly, bypassing any source registered with
+/// [`set_source`](crate::set_source). On Windows the name is the adapter name that
+/// [`get_if_addrs`](crate::get_if_addrs) reports.
+pub fn interface_exists(name: &str) -> io::Result<bool> {
+    os::interface_exists(name)
+}
+
+/// Count the interfaces of this host, including those without addresses.
+///
+/// Like [`interface_exists`], this asks the operating system directly.
+pub fn interface_count() -> io::Result<usize> {
+    os::interface_count()
+}
+
+/// List the name and index of every interface of this host, including those without
+/// addresses, e.g. to offer a choice of interfaces before enumerating their addresses.
+///
+/// Like [`interface_exists`], this asks the operating system directly.
+pub fn get_if_names() -> io::Result<Vec<(String, u32)>> {
+    os::get_if_names()
+}
+
+#[cfg(not(windows))]
+mod os {
+    #[cfg(not(target_os = "android"))]
+    use std::ffi::CStr;
+    use std::ffi::CString;
+    use std::io;
+
+    #[allow(unsafe_code)]
+    pub fn interface_exists(name: &str) -> io::Result<bool> {
+        let name = match CString::new(name) {
+            Ok(name) => name,
+            Err(_) => return Ok(false),
+        };
+        Ok(unsafe { libc::if_nametoindex(name.as_ptr()) } != 0)
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn interface_count() -> io::Result<usize> {
+        crate::procfs::interface_count()
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    #[allow(unsafe_code)]
+    pub fn interface_count() -> io::Result<usize> {
+        let names = unsafe { libc::if_nameindex() };
+        if names.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        let mut count = 0;
+        while unsafe { (*names.add(count)).if_index } != 0 {
+            count += 1;
+        }
+        unsafe { libc::if_freenameindex(names) };
+        Ok(count)
+    }
+
+    // if_nameindex needs Android API 24, like getifaddrs.
+    #[cfg(target_os = "android")]
+    #[allow(unsafe_code)]
+    pub fn get_if_names() -> io::Result<Vec<(String, u32)>> {
+        let mut ret = Vec::new();
+        for name in crate::procfs::interface_names()? {
+            let c_name = match CString::new(name.as_str()) {
+                Ok(c_name) => c_name,
+                Err(_) => continue,
+            };
+            match unsafe { libc::if_nametoindex(c_name.as_ptr()) } {
+                0 => continue,
+                index => ret.push((name, index)),
+            }
+        }
+        Ok(ret)
+    }
+
+    #[cfg(not(target_os = "android"))]
+    #[allow(unsafe_code)]
+    pub fn get_if_names() -> io::Result<Vec<(String, u32)>> {
+        let names = unsafe { libc::if_nameindex() };
+        if names.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        let mut ret = Vec::new();
+        let mut entry = names;
+        while unsafe { (*entry).if_index } != 0 {
+            let name = unsafe { CStr::from_ptr((*entry).if_name) }
+                .to_string_lossy()
+                .into_owned();
+            ret.push((name, unsafe { (*entry).if_index }));
+            entry = unsafe { entry.add(1) };
+        }
+        unsafe { libc::if_freenameindex(names) };
+        Ok(ret)
+    }
+}
+
+#[cfg(windows)]
+mod os {
+    use crate::windows;
+    use std::io;
+
+    pub fn interface_exists(name: &str) -> io::Result<bool> {
+        Ok(windows::adapters_only()?
+            .iter()
+            .any(|ifaddr| ifaddr.name.eq_ignore_ascii_case(name)))
+    }
+
+    pub fn interface_count() -> io::Result<usize> {
+        Ok(windows::adapters_only()?.len())
+    }
+
+    pub fn get_if_names() -> io::Result<Vec<(String, u32)>> {
+        Ok(windows::adapters_only()?
+            .into_iter()
+            .map(|ifaddr| {
+                let index = match ifaddr.if_index {
+                    0 => ifaddr.ipv6_if_index,
+                    index => index,
+                };
+                (ifaddr.name, index)
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,100 @@
+// Copyright 2018 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::Cidr;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, ToSocketAddrs};
+
+/// The name DNS64 resolvers synthesize AAAA records for, per RFC 7050.
+const IPV4ONLY_ARPA: &str = "ipv4only.arpa";
+
+/// The well-known IPv4 addresses of `ipv4only.arpa`.
+const WELL_KNOWN: [Ipv4Addr; 2] = [Ipv4Addr::new(192, 0, 0, 170), Ipv4Addr::new(192, 0, 0, 171)];
+
+/// The prefix lengths RFC 6052 defines for IPv4-embedded IPv6 addresses.
+const PREFIX_LENS: [u8; 6] = [96, 64, 56, 48, 40, 32];
+
+/// Discover the NAT64 prefix of this network as described in RFC 7050, e.g. `64:ff9b::/96`,
+/// so IPv4 destinations can be reached through it from an IPv6-only network.
+///
+/// This resolves `ipv4only.arpa` through the system resolver and looks for the well-known
+/// IPv4 addresses embedded in the returned IPv6 addresses. `None` if there is no DNS64 on the
+/// path, or without the globally routable IPv6 address NAT64 needs, in which case no lookup
+/// is made.
+pub fn nat64_prefix() -> io::Result<Option<Cidr>> {
+    if !crate::has_global_ipv6()? {
+        return Ok(None);
+    }
+    Ok((IPV4ONLY_ARPA, 0)
+        .to_socket_addrs()?
+        .filter_map(|addr| match addr.ip() {
+            IpAddr::V6(ip) => prefix_of(ip),
+            IpAddr::V4(_) => None,
+        })
+        .next())
+}
+
+/// The NAT64 prefix of `ip` if it embeds one of the well-known addresses.
+fn prefix_of(ip: Ipv6Addr) -> Option<Cidr> {
+    PREFIX_LENS.iter().find_map(|&prefix_len| {
+        let embedded = extract_ipv4(ip, prefix_len)?;
+        if WELL_KNOWN.contains(&embedded) {
+            Cidr::new(IpAddr::V6(ip), prefix_len).map(|cidr| cidr.network())
+        } else {
+            None
+        }
+    })
+}
+
+/// Read the IPv4 address embedded after a `prefix_len` bit prefix, skipping bits 64 to 71,
+/// which RFC 6052 reserves and requires to be zero.
+fn extract_ipv4(ip: Ipv6Addr, prefix_len: u8) -> Option<Ipv4Addr> {
+    let octets = ip.octets();
+    if prefix_len < 96 && octets[8] != 0 {
+        return None;
+    }
+    let start = usize::from(prefix_len / 8);
+    let mut embedded = [0; 4];
+    let positions = (start..16).filter(|&i| i != 8);
+    for (dst, i) in embedded.iter_mut().zip(positions) {
+        *dst = octets[i];
+    }
+    Some(Ipv4Addr::from(embedded))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::extract_ipv4;
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn test_extract_ipv4() {
+        // The examples of RFC 6052, section 2.4.
+        let examples = [
+            ("2001:db8:c000:221::", 32),
+            ("2001:db8:1c0:2:21::", 40),
+            ("2001:db8:122:c000:2:2100::", 48),
+            ("2001:db8:122:3c0:0:221::", 56),
+            ("2001:db8:122:344:c0:2:2100:0", 64),
+            ("2001:db8:122:344::192.0.2.33", 96),
+        ];
+        for &(ip, prefix_len) in &examples {
+            assert_eq!(
+                Some(Ipv4Addr::new(192, 0, 2, 33)),
+                extract_ipv4(ip.parse().unwrap(), prefix_len),
+                "{}",
+                ip
+            );
+        }
+        assert_eq!(
+            None,
+            extract_ipv4("2001:db8::ff00:0:0:0".parse().unwrap(), 32)
+        );
+    }
+}
@@ -0,0 +1,433 @@
+// Copyright 2018 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::nlmsg::{self, attrs, u32_at, NLM_F_DUMP};
+use crate::{IfAddrsWarning, IfAddrsWarningKind};
+use rustix::fd::OwnedFd;
+use rustix::net::netlink::SocketAddrNetlink;
+use rustix::net::{
+    recv, sendto, socket_with, AddressFamily, RecvFlags, SendFlags, SocketFlags, SocketType,
+};
+use std::convert::TryInto;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+const RTM_NEWLINK: u16 = 16;
+const RTM_GETLINK: u16 = 18;
+const RTM_NEWADDR: u16 = 20;
+const RTM_GETADDR: u16 = 22;
+
+const RTM_NEWROUTE: u16 = 24;
+const RTM_GETROUTE: u16 = 26;
+
+const IFINFOMSG_LEN: usize = 16;
+const IFLA_ADDRESS: u16 = 1;
+const IFLA_IFNAME: u16 = 3;
+const IFLA_TXQLEN: u16 = 13;
+const IFLA_IFALIAS: u16 = 20;
+const IFLA_GROUP: u16 = 27;
+const IFLA_PROMISCUITY: u16 = 30;
+const IFLA_CARRIER: u16 = 33;
+
+const IFF_UP: u32 = 0x1;
+
+const IFADDRMSG_LEN: usize = 8;
+const IFA_ADDRESS: u16 = 1;
+const IFA_LOCAL: u16 = 2;
+const IFA_LABEL: u16 = 3;
+const IFA_BROADCAST: u16 = 4;
+const IFA_CACHEINFO: u16 = 6;
+
+const RTMSG_LEN: usize = 12;
+const RTA_OIF: u16 = 4;
+const RTA_GATEWAY: u16 = 5;
+const RTA_CACHEINFO: u16 = 12;
+const RTPROT_RA: u8 = 9;
+const AF_INET6: u8 = 10;
+
+/// One link record from a `RTM_GETLINK` dump.
+pub struct LinkMsg {
+    pub name: String,
+    /// The bytes of the name if they are not valid UTF-8.
+    pub raw_name: Option<Vec<u8>>,
+    pub index: u32,
+    pub flags: u32,
+    pub hw_addr: Option<[u8; 6]>,
+    pub alias: Option<String>,
+    pub txqueuelen: Option<u32>,
+    pub group: Option<u32>,
+    pub promiscuity: Option<u32>,
+    pub carrier: Option<bool>,
+}
+
+/// One address record from a `RTM_GETADDR` dump.
+pub struct IfAddrMsg {
+    pub name: String,
+    /// The bytes of the name if they are not valid UTF-8.
+    pub raw_name: Option<Vec<u8>>,
+    pub index: u32,
+    pub prefixlen: u8,
+    pub address: IpAddr,
+    pub broadcast: Option<IpAddr>,
+    /// When the address was added, in hundredths of a second since boot.
+    pub created: Option<u32>,
+    /// When the address was last updated, in hundredths of a second since boot.
+    pub updated: Option<u32>,
+}
+
+pub struct IfAddrs {
+    links: Vec<LinkMsg>,
+    inner: Vec<IfAddrMsg>,
+    /// The address records that were skipped, and why.
+    warnings: Vec<IfAddrsWarning>,
+}
+
+impl IfAddrs {
+    pub fn new() -> io::Result<Self> {
+        Self::dump_with(true)
+    }
+
+    /// Dump only the links, leaving the address list empty.
+    pub fn links_only() -> io::Result<Self> {
+        Self::dump_with(false)
+    }
+
+    fn dump_with(with_addrs: bool) -> io::Result<Self> {
+        let fd = socket_with(
+            AddressFamily::NETLINK,
+            SocketType::RAW,
+            SocketFlags::CLOEXEC,
+            None,
+        )?;
+
+        let mut links = Vec::new();
+        for (msg_type, payload) in dump(&fd, RTM_GETLINK, 1)? {
+            if msg_type != RTM_NEWLINK || payload.len() < IFINFOMSG_LEN {
+                continue;
+            }
+            let index = u32_at(&payload, 4);
+            let flags = u32_at(&payload, 8);
+            let mut name = None;
+            let mut raw_name = None;
+            let mut hw_addr = None;
+            let mut alias = None;
+            let mut txqueuelen = None;
+            let mut group = None;
+            let mut promiscuity = None;
+            let mut carrier = None;
+            for (attr_type, value) in attrs(&payload[IFINFOMSG_LEN..]) {
+                match attr_type {
+                    IFLA_IFNAME => {
+                        name = Some(c_string(value));
+                        raw_name = non_utf8(value);
+                    }
+                    IFLA_ADDRESS if value.len() == 6 => hw_addr = value.try_into().ok(),
+                    IFLA_TXQLEN if value.len() == 4 => txqueuelen = Some(u32_at(value, 0)),
+                    IFLA_GROUP if value.len() == 4 => group = Some(u32_at(value, 0)),
+                    IFLA_PROMISCUITY if value.len() == 4 => promiscuity = Some(u32_at(value, 0)),
+                    // Like sysfs, only report the carrier of devices that are up.
+                    IFLA_CARRIER if !value.is_empty() && flags & IFF_UP != 0 => {
+                        carrier = Some(value[0] != 0)
+                    }
+                    IFLA_IFALIAS => alias = Some(c_string(value)).filter(|a| !a.is_empty()),
+                    _ => {}
+                }
+            }
+            if let Some(name) = name {
+                links.push(LinkMsg {
+                    name,
+                    raw_name,
+                    index,
+                    flags,
+                    hw_addr,
+                    alias,
+                    txqueuelen,
+                    group,
+                    promiscuity,
+                    carrier,
+                });
+            }
+        }
+
+        let mut inner = Vec::new();
+        let mut warnings = Vec::new();
+        if !with_addrs {
+            return Ok(Self {
+                links,
+                inner,
+                warnings,
+            });
+        }
+        for (msg_type, payload) in dump(&fd, RTM_GETADDR, 2)? {
+            if msg_type != RTM_NEWADDR {
+                continue;
+            }
+            inner.extend(parse_addr(&payload, &links, &mut warnings));
+        }
+
+        Ok(Self {
+            links,
+            inner,
+            warnings,
+        })
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, IfAddrMsg> {
+        self.inner.iter()
+    }
+
+    pub fn links(&self) -> std::slice::Iter<'_, LinkMsg> {
+        self.links.iter()
+    }
+
+    pub fn warnings(&self) -> &[IfAddrsWarning] {
+        &self.warnings
+    }
+}
+
+/// Read the payload of one `RTM_NEWADDR` message, naming the address after its label or else
+/// its link in `links`. Returns `None`, adding to `warnings`, if the address is unusable.
+fn parse_addr(
+    payload: &[u8],
+    links: &[LinkMsg],
+    warnings: &mut Vec<IfAddrsWarning>,
+) -> Option<IfAddrMsg> {
+    if payload.len() < IFADDRMSG_LEN {
+        warnings.push(IfAddrsWarning::new(
+            None,
+            None,
+            IfAddrsWarningKind::MalformedAddress,
+        ));
+        return None;
+    }
+    let prefixlen = payload[1];
+    let index = u32_at(payload, 4);
+
+    let mut address = None;
+    let mut local = None;
+    let mut broadcast = None;
+    let mut label = None;
+    let mut created = None;
+    let mut updated = None;
+    for (attr_type, value) in attrs(&payload[IFADDRMSG_LEN..]) {
+        match attr_type {
+            IFA_ADDRESS => address = to_ipaddr(value),
+            IFA_LOCAL => local = to_ipaddr(value),
+            IFA_BROADCAST => broadcast = to_ipaddr(value),
+            IFA_LABEL => label = Some((c_string(value), non_utf8(value))),
+            // struct ifa_cacheinfo: preferred and valid lifetimes, cstamp, tstamp.
+            IFA_CACHEINFO if value.len() >= 16 => {
+                created = Some(u32_at(value, 8));
+                updated = Some(u32_at(value, 12));
+            }
+            _ => {}
+        }
+    }
+
+    // Like getifaddrs, prefer the local address: on point-to-point
+    // links IFA_ADDRESS holds the peer.
+    let (name, raw_name) = match label {
+        Some(label) => label,
+        None => match links.iter().find(|link| link.index == index) {
+            Some(link) => (link.name.clone(), link.raw_name.clone()),
+            None => {
+                warnings.push(IfAddrsWarning::new(
+                    None,
+                    Some(index),
+                    IfAddrsWarningKind::UnknownInterface,
+                ));
+                return None;
+            }
+        },
+    };
+    let address = match local.or(address) {
+        Some(address) => address,
+        None => {
+            warnings.push(IfAddrsWarning::new(
+                Some(&name),
+                Some(index),
+                IfAddrsWarningKind::MalformedAddress,
+            ));
+            return None;
+        }
+    };
+
+    Some(IfAddrMsg {
+        name,
+        raw_name,
+        index,
+        prefixlen,
+        address,
+        broadcast,
+        created,
+        updated,
+    })
+}
+
+/// One IPv6 default route from a `RTM_GETROUTE` dump that was learned from a Router
+/// Advertisement.
+pub struct RaRouteMsg {
+    pub gateway: Ipv6Addr,
+    pub index: u32,
+    /// The remaining lifetime in hundredths of a second, `None` if the route does not expire.
+    pub expires: Option<u32>,
+}
+
+/// Dump the routes and keep the IPv6 default routes with `RTPROT_RA` as their protocol.
+pub fn ra_default_routes() -> io::Result<Vec<RaRouteMsg>> {
+    let fd = socket_with(
+        AddressFamily::NETLINK,
+        SocketType::RAW,
+        SocketFlags::CLOEXEC,
+        None,
+    )?;
+
+    let mut ret = Vec::new();
+    for (msg_type, payload) in dump(&fd, RTM_GETROUTE, 1)? {
+        if msg_type != RTM_NEWROUTE || payload.len() < RTMSG_LEN {
+            continue;
+        }
+        // struct rtmsg: rtm_family, rtm_dst_len, rtm_src_len, rtm_tos, rtm_table,
+        // rtm_protocol, rtm_scope, rtm_type, rtm_flags.
+        if payload[0] != AF_INET6 || payload[1] != 0 || payload[5] != RTPROT_RA {
+            continue;
+        }
+        let mut gateway = None;
+        let mut index = None;
+        let mut expires = None;
+        for (attr_type, value) in attrs(&payload[RTMSG_LEN..]) {
+            match attr_type {
+                RTA_OIF if value.len() >= 4 => index = Some(u32_at(value, 0)),
+                RTA_GATEWAY => {
+                    if let Some(IpAddr::V6(ip)) = to_ipaddr(value) {
+                        gateway = Some(ip);
+                    }
+                }
+                // struct rta_cacheinfo: rta_clntref, rta_lastuse, rta_expires, ...
+                RTA_CACHEINFO if value.len() >= 12 => {
+                    let remaining = u32_at(value, 8) as i32;
+                    expires = if remaining > 0 {
+                        Some(remaining as u32)
+                    } else {
+                        None
+                    };
+                }
+                _ => {}
+            }
+        }
+        if let (Some(gateway), Some(index)) = (gateway, index) {
+            ret.push(RaRouteMsg {
+                gateway,
+                index,
+                expires,
+            });
+        }
+    }
+    Ok(ret)
+}
+
+/// Send a dump request of the given type and collect `(type, payload)` for
+/// every reply message up to `NLMSG_DONE`.
+fn dump(fd: &OwnedFd, msg_type: u16, seq: u32) -> io::Result<Vec<(u16, Vec<u8>)>> {
+    // A zeroed family-agnostic request body, which is large enough for both ifinfomsg and
+    // ifaddrmsg.
+    let request = nlmsg::request(msg_type, NLM_F_DUMP, seq, &[0; IFINFOMSG_LEN]);
+    nlmsg::transact(
+        &request,
+        |msg| {
+            sendto(fd, msg, SendFlags::empty(), &SocketAddrNetlink::new(0, 0))?;
+            Ok(())
+        },
+        |buf| Ok(recv(fd, buf, RecvFlags::empty())?.0),
+    )
+}
+
+fn c_bytes(value: &[u8]) -> &[u8] {
+    let end = value.iter().position(|&b| b == 0).unwrap_or(value.len());
+    &value[..end]
+}
+
+fn c_string(value: &[u8]) -> String {
+    String::from_utf8_lossy(c_bytes(value)).into_owned()
+}
+
+/// The bytes of a NUL-terminated string attribute, if `c_string` cannot keep them as they are.
+fn non_utf8(value: &[u8]) -> Option<Vec<u8>> {
+    let bytes = c_bytes(value);
+    match std::str::from_utf8(bytes) {
+        Ok(_) => None,
+        Err(_) => Some(bytes.to_vec()),
+    }
+}
+
+fn to_ipaddr(value: &[u8]) -> Option<IpAddr> {
+    match value.len() {
+        4 => {
+            let octets: [u8; 4] = value.try_into().unwrap();
+            Some(IpAddr::V4(Ipv4Addr::from(octets)))
+        }
+        16 => {
+            let octets: [u8; 16] = value.try_into().unwrap();
+            Some(IpAddr::V6(Ipv6Addr::from(octets)))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_addr, IFA_ADDRESS, IFA_LABEL};
+    use crate::nlmsg::push_attr;
+    use crate::IfAddrsWarningKind;
+    use std::net::IpAddr;
+
+    /// An `ifaddrmsg` for an IPv4 address on interface 2, followed by `attrs`.
+    fn addr_msg(attrs: &[(u16, &[u8])]) -> Vec<u8> {
+        let mut payload = vec![2, 24, 0, 0];
+        payload.extend_from_slice(&2u32.to_ne_bytes());
+        for &(attr_type, value) in attrs {
+            push_attr(&mut payload, attr_type, value);
+        }
+        payload
+    }
+
+    #[test]
+    fn test_parse_addr() {
+        let mut warnings = Vec::new();
+        let payload = addr_msg(&[(IFA_LABEL, b"eth0\0"), (IFA_ADDRESS, &[192, 0, 2, 1])]);
+        let msg = parse_addr(&payload, &[], &mut warnings).unwrap();
+        assert_eq!(msg.name, "eth0");
+        assert_eq!(msg.index, 2);
+        assert_eq!(msg.prefixlen, 24);
+        assert_eq!(msg.address, IpAddr::from([192, 0, 2, 1]));
+        assert!(warnings.is_empty());
+
+        // A truncated address attribute.
+        let payload = addr_msg(&[(IFA_LABEL, b"eth0\0"), (IFA_ADDRESS, &[192, 0, 2])]);
+        assert!(parse_addr(&payload, &[], &mut warnings).is_none());
+        // A truncated message.
+        assert!(parse_addr(&payload[..6], &[], &mut warnings).is_none());
+        // No label, and no link with the index.
+        let payload = addr_msg(&[(IFA_ADDRESS, &[192, 0, 2, 1])]);
+        assert!(parse_addr(&payload, &[], &mut warnings).is_none());
+
+        let kinds: Vec<_> = warnings
+            .iter()
+            .map(|w| (w.name.as_deref(), w.index, w.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                (Some("eth0"), Some(2), IfAddrsWarningKind::MalformedAddress),
+                (None, None, IfAddrsWarningKind::MalformedAddress),
+                (None, Some(2), IfAddrsWarningKind::UnknownInterface),
+            ]
+        );
+    }
+}
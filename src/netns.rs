@@ -0,0 +1,57 @@
+// Copyright 2018 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Enumeration inside other Linux network namespaces, e.g. those of containers.
+//!
+//! Each call switches a short-lived helper thread into the namespace with `setns(2)` and
+//! enumerates from there, so the namespace of the calling thread is never touched. This needs
+//! `CAP_SYS_ADMIN`. Details only available through sysfs, such as [`Interface::alias`] with
+//! the getifaddrs backend or [`Interface::virtual_device`], are not reported for other
+//! namespaces, as sysfs always shows the namespace it was mounted from.
+
+use crate::Interface;
+use std::fs::File;
+use std::io;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::panic;
+use std::path::Path;
+use std::thread;
+
+/// Get the interfaces of the network namespace bound at `path`, e.g. `/var/run/netns/<name>`
+/// as created by `ip netns add`.
+pub fn get_if_addrs_in_netns_path<P: AsRef<Path>>(path: P) -> io::Result<Vec<Interface>> {
+    let file = File::open(path)?;
+    get_if_addrs_in_netns_fd(file.as_raw_fd())
+}
+
+/// Get the interfaces of the network namespace of the process `pid`.
+pub fn get_if_addrs_in_netns_pid(pid: u32) -> io::Result<Vec<Interface>> {
+    get_if_addrs_in_netns_path(format!("/proc/{}/ns/net", pid))
+}
+
+/// Get the interfaces of the network namespace referred to by the open file descriptor `fd`,
+/// which must stay open for the duration of the call.
+pub fn get_if_addrs_in_netns_fd(fd: RawFd) -> io::Result<Vec<Interface>> {
+    thread::Builder::new()
+        .name("if-addrs-netns".to_string())
+        .spawn(move || enter(fd).and_then(|()| crate::os_get_if_addrs()))?
+        .join()
+        .unwrap_or_else(|panic| panic::resume_unwind(panic))
+}
+
+/// Switch the current thread into the network namespace `fd`. The thread must not be reused
+/// for anything else afterwards.
+#[allow(unsafe_code)]
+fn enter(fd: RawFd) -> io::Result<()> {
+    if unsafe { libc::setns(fd, libc::CLONE_NEWNET) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    crate::procfs::set_foreign_netns();
+    Ok(())
+}
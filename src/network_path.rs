@@ -0,0 +1,120 @@
+// Copyright 2018 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Metered-link flags from Apple's Network framework.
+//!
+//! A network path is expensive when it goes over cellular or a personal hotspot, and
+//! constrained when the user enabled Low Data Mode for it. The flags are read from one
+//! `nw_path_monitor` per interface type and attributed to every interface on that path.
+
+use block::ConcreteBlock;
+use libc::{c_char, c_void};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ffi::CStr;
+use std::io;
+use std::ptr;
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// How long to wait for a monitor to report its first path.
+const UPDATE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// `nw_interface_type_t` values: other, wifi, cellular, wired and loopback.
+const INTERFACE_TYPES: [i32; 5] = [0, 1, 2, 3, 4];
+
+/// Metered-link flags of the path an interface belongs to.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+pub struct PathFlags {
+    /// Whether the path is considered expensive, such as cellular or a personal hotspot.
+    pub expensive: bool,
+    /// Whether the path is constrained by Low Data Mode.
+    pub constrained: bool,
+}
+
+/// Get the metered-link flags of every interface that is currently part of a usable path,
+/// keyed by BSD name (e.g. `en0`).
+///
+/// Interfaces without a usable path are absent from the map.
+#[allow(unsafe_code)]
+pub fn get_path_flags() -> io::Result<HashMap<String, PathFlags>> {
+    let mut ret = HashMap::new();
+    let queue =
+        unsafe { dispatch_queue_create(b"if-addrs\0".as_ptr() as *const c_char, ptr::null_mut()) };
+    if queue.is_null() {
+        return Err(io::Error::other("failed to create a dispatch queue"));
+    }
+
+    for &interface_type in INTERFACE_TYPES.iter() {
+        let (tx, rx) = mpsc::channel();
+        let tx = Mutex::new(tx);
+        let handler = ConcreteBlock::new(move |path: *mut c_void| {
+            let flags = PathFlags {
+                expensive: unsafe { nw_path_is_expensive(path) },
+                constrained: unsafe { nw_path_is_constrained(path) },
+            };
+            let names = RefCell::new(Vec::new());
+            let enumerate = ConcreteBlock::new(|interface: *mut c_void| {
+                let name = unsafe { nw_interface_get_name(interface) };
+                if !name.is_null() {
+                    let name = unsafe { CStr::from_ptr(name) };
+                    names.borrow_mut().push(name.to_string_lossy().into_owned());
+                }
+                true
+            });
+            unsafe { nw_path_enumerate_interfaces(path, &*enumerate as *const _ as *const c_void) };
+            drop(enumerate);
+            if let Ok(tx) = tx.lock() {
+                let _ = tx.send((flags, names.into_inner()));
+            }
+        })
+        .copy();
+
+        unsafe {
+            let monitor = nw_path_monitor_create_with_type(interface_type);
+            if monitor.is_null() {
+                continue;
+            }
+            nw_path_monitor_set_queue(monitor, queue);
+            nw_path_monitor_set_update_handler(monitor, &*handler as *const _ as *const c_void);
+            nw_path_monitor_start(monitor);
+            let update = rx.recv_timeout(UPDATE_TIMEOUT);
+            nw_path_monitor_cancel(monitor);
+            nw_release(monitor);
+            if let Ok((flags, names)) = update {
+                for name in names {
+                    ret.insert(name, flags);
+                }
+            }
+        }
+    }
+
+    unsafe { dispatch_release(queue) };
+    Ok(ret)
+}
+
+#[link(name = "Network", kind = "framework")]
+extern "C" {
+    fn nw_path_monitor_create_with_type(interface_type: i32) -> *mut c_void;
+    fn nw_path_monitor_set_queue(monitor: *mut c_void, queue: *mut c_void);
+    fn nw_path_monitor_set_update_handler(monitor: *mut c_void, handler: *const c_void);
+    fn nw_path_monitor_start(monitor: *mut c_void);
+    fn nw_path_monitor_cancel(monitor: *mut c_void);
+    fn nw_path_is_expensive(path: *mut c_void) -> bool;
+    fn nw_path_is_constrained(path: *mut c_void) -> bool;
+    fn nw_path_enumerate_interfaces(path: *mut c_void, enumerate_block: *const c_void);
+    fn nw_interface_get_name(interface: *mut c_void) -> *const c_char;
+    fn nw_release(object: *mut c_void);
+}
+
+extern "C" {
+    fn dispatch_queue_create(label: *const c_char, attr: *mut c_void) -> *mut c_void;
+    fn dispatch_release(object: *mut c_void);
+}
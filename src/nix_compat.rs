@@ -0,0 +1,92 @@
+// Copyright 2018 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::{IfAddr, Ifv4Addr, Ifv6Addr, Interface};
+use core::convert::TryFrom;
+#[cfg(not(feature = "std"))]
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use nix::ifaddrs::InterfaceAddress;
+use nix::net::if_::InterfaceFlags;
+use nix::sys::socket::SockaddrStorage;
+#[cfg(feature = "std")]
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+/// Convert an entry of `nix::ifaddrs::getifaddrs()`. Entries without an IPv4 or IPv6 address,
+/// such as link-layer ones, are handed back as the error.
+///
+/// Unlike [`get_if_addrs`](crate::get_if_addrs), link-local IPv6 addresses are kept and the
+/// interface index and the other lookups are left `None`.
+impl TryFrom<InterfaceAddress> for Interface {
+    type Error = InterfaceAddress;
+
+    fn try_from(ifaddr: InterfaceAddress) -> Result<Self, Self::Error> {
+        let netmask = ifaddr.netmask.as_ref().and_then(to_ipaddr);
+        let broadcast = if ifaddr.flags.contains(InterfaceFlags::IFF_BROADCAST) {
+            ifaddr.broadcast.as_ref().and_then(to_ipaddr)
+        } else {
+            None
+        };
+        let addr = match ifaddr.address.as_ref().and_then(to_ipaddr) {
+            Some(IpAddr::V4(ip)) => IfAddr::V4(Ifv4Addr {
+                ip,
+                netmask: match netmask {
+                    Some(IpAddr::V4(netmask)) => netmask,
+                    _ => Ipv4Addr::new(0, 0, 0, 0),
+                },
+                broadcast: match broadcast {
+                    Some(IpAddr::V4(broadcast)) => Some(broadcast),
+                    _ => None,
+                },
+            }),
+            Some(IpAddr::V6(ip)) => IfAddr::V6(Ifv6Addr {
+                ip,
+                netmask: match netmask {
+                    Some(IpAddr::V6(netmask)) => netmask,
+                    _ => Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 0),
+                },
+                broadcast: None,
+            }),
+            None => return Err(ifaddr),
+        };
+        let mut iface = Interface::new(ifaddr.interface_name, addr, None);
+        iface.flags = Some(ifaddr.flags.bits() as u32);
+        Ok(iface)
+    }
+}
+
+/// Convert back into the shape of a `nix::ifaddrs::getifaddrs()` entry, with port-less
+/// addresses and no point-to-point destination.
+impl From<&Interface> for InterfaceAddress {
+    fn from(iface: &Interface) -> Self {
+        let (netmask, broadcast) = match iface.addr {
+            IfAddr::V4(ref addr) => (IpAddr::V4(addr.netmask), addr.broadcast.map(IpAddr::V4)),
+            IfAddr::V6(ref addr) => (IpAddr::V6(addr.netmask), addr.broadcast.map(IpAddr::V6)),
+        };
+        InterfaceAddress {
+            interface_name: iface.name.clone(),
+            flags: InterfaceFlags::from_bits_truncate(iface.flags.unwrap_or(0) as _),
+            address: Some(to_storage(iface.ip())),
+            netmask: Some(to_storage(netmask)),
+            broadcast: broadcast.map(to_storage),
+            destination: None,
+        }
+    }
+}
+
+fn to_ipaddr(storage: &SockaddrStorage) -> Option<IpAddr> {
+    match (storage.as_sockaddr_in(), storage.as_sockaddr_in6()) {
+        (Some(sin), _) => Some(IpAddr::V4(sin.ip())),
+        (_, Some(sin6)) => Some(IpAddr::V6(sin6.ip())),
+        _ => None,
+    }
+}
+
+fn to_storage(ip: IpAddr) -> SockaddrStorage {
+    SockaddrStorage::from(SocketAddr::new(ip, 0))
+}
@@ -0,0 +1,168 @@
+// Copyright 2018 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Netlink message framing, shared by the rtnetlink and generic netlink users.
+
+use std::convert::TryInto;
+use std::io;
+#[cfg(any(feature = "wireless", feature = "config"))]
+use std::os::unix::io::{AsRawFd, FromRawFd, OwnedFd};
+
+pub const NLMSG_HDRLEN: usize = 16;
+pub const NLMSG_ERROR: u16 = 2;
+pub const NLMSG_DONE: u16 = 3;
+pub const NLM_F_REQUEST: u16 = 0x1;
+pub const NLM_F_DUMP: u16 = 0x300;
+
+/// Build a request of `msg_type`: an `nlmsghdr` with `NLM_F_REQUEST` and `flags`, followed by
+/// `body`.
+pub fn request(msg_type: u16, flags: u16, seq: u32, body: &[u8]) -> Vec<u8> {
+    let len = NLMSG_HDRLEN + body.len();
+    let mut msg = Vec::with_capacity(len);
+    msg.extend_from_slice(&(len as u32).to_ne_bytes());
+    msg.extend_from_slice(&msg_type.to_ne_bytes());
+    msg.extend_from_slice(&(NLM_F_REQUEST | flags).to_ne_bytes());
+    msg.extend_from_slice(&seq.to_ne_bytes());
+    msg.extend_from_slice(&0u32.to_ne_bytes());
+    msg.extend_from_slice(body);
+    msg
+}
+
+/// Append an attribute to `msg`, padded to four bytes.
+#[cfg_attr(not(any(feature = "wireless", feature = "config")), allow(dead_code))]
+pub fn push_attr(msg: &mut Vec<u8>, attr_type: u16, value: &[u8]) {
+    msg.extend_from_slice(&((4 + value.len()) as u16).to_ne_bytes());
+    msg.extend_from_slice(&attr_type.to_ne_bytes());
+    msg.extend_from_slice(value);
+    msg.resize(align(msg.len()), 0);
+}
+
+/// Send `request` and collect `(type, payload)` for every reply to it, until the kernel ends a
+/// dump with `NLMSG_DONE`, acknowledges the request, or answers a request that is not a dump.
+pub fn transact(
+    request: &[u8],
+    mut send: impl FnMut(&[u8]) -> io::Result<()>,
+    mut recv: impl FnMut(&mut [u8]) -> io::Result<usize>,
+) -> io::Result<Vec<(u16, Vec<u8>)>> {
+    let flags = u16::from_ne_bytes([request[6], request[7]]);
+    let seq = u32_at(request, 8);
+    send(request)?;
+
+    let mut ret = Vec::new();
+    let mut buf = vec![0u8; 32 * 1024];
+    loop {
+        let n = recv(&mut buf)?;
+        let mut msgs = &buf[..n];
+        while msgs.len() >= NLMSG_HDRLEN {
+            let msg_len = u32_at(msgs, 0) as usize;
+            if msg_len < NLMSG_HDRLEN || msg_len > msgs.len() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "truncated netlink message",
+                ));
+            }
+            let msg_type = u16::from_ne_bytes([msgs[4], msgs[5]]);
+            let payload = &msgs[NLMSG_HDRLEN..msg_len];
+            if u32_at(msgs, 8) == seq {
+                match msg_type {
+                    NLMSG_DONE => return Ok(ret),
+                    NLMSG_ERROR => {
+                        let errno = if payload.len() >= 4 {
+                            i32::from_ne_bytes(payload[..4].try_into().unwrap())
+                        } else {
+                            0
+                        };
+                        if errno != 0 {
+                            return Err(io::Error::from_raw_os_error(-errno));
+                        }
+                        return Ok(ret);
+                    }
+                    _ => ret.push((msg_type, payload.to_vec())),
+                }
+            }
+            msgs = &msgs[align(msg_len).min(msgs.len())..];
+        }
+        // Only dumps are terminated by NLMSG_DONE.
+        if flags & NLM_F_DUMP == 0 && !ret.is_empty() {
+            return Ok(ret);
+        }
+    }
+}
+
+/// Iterate the attribute list in `buf` as `(type, value)` pairs.
+#[cfg_attr(not(any(feature = "rustix", feature = "wireless")), allow(dead_code))]
+pub fn attrs(mut buf: &[u8]) -> impl Iterator<Item = (u16, &[u8])> {
+    std::iter::from_fn(move || {
+        if buf.len() < 4 {
+            return None;
+        }
+        let len = u16::from_ne_bytes([buf[0], buf[1]]) as usize;
+        if len < 4 || len > buf.len() {
+            return None;
+        }
+        // Mask out NLA_F_NESTED and NLA_F_NET_BYTEORDER.
+        let attr_type = u16::from_ne_bytes([buf[2], buf[3]]) & 0x3fff;
+        let value = &buf[4..len];
+        buf = &buf[align(len).min(buf.len())..];
+        Some((attr_type, value))
+    })
+}
+
+pub fn align(len: usize) -> usize {
+    (len + 3) & !3
+}
+
+pub fn u32_at(buf: &[u8], offset: usize) -> u32 {
+    u32::from_ne_bytes(buf[offset..offset + 4].try_into().unwrap())
+}
+
+/// A netlink socket opened through libc.
+#[cfg(any(feature = "wireless", feature = "config"))]
+pub struct Socket(OwnedFd);
+
+#[cfg(any(feature = "wireless", feature = "config"))]
+impl Socket {
+    /// Open a netlink socket for `protocol`, such as `NETLINK_ROUTE`.
+    #[allow(unsafe_code)]
+    pub fn open(protocol: libc::c_int) -> io::Result<Self> {
+        let fd = unsafe {
+            libc::socket(
+                libc::AF_NETLINK,
+                libc::SOCK_RAW | libc::SOCK_CLOEXEC,
+                protocol,
+            )
+        };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Socket(unsafe { OwnedFd::from_raw_fd(fd) }))
+    }
+
+    /// Send `request` and collect the replies, see [`transact`].
+    #[allow(unsafe_code)]
+    pub fn transact(&self, request: &[u8]) -> io::Result<Vec<(u16, Vec<u8>)>> {
+        let fd = self.0.as_raw_fd();
+        transact(
+            request,
+            |msg| {
+                if unsafe { libc::send(fd, msg.as_ptr() as *const _, msg.len(), 0) } < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(())
+            },
+            |buf| {
+                let n = unsafe { libc::recv(fd, buf.as_mut_ptr() as *mut _, buf.len(), 0) };
+                if n < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                Ok(n as usize)
+            },
+        )
+    }
+}
@@ -0,0 +1,186 @@
+// Copyright 2018 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::Interface;
+use std::io;
+use std::net::IpAddr;
+
+/// Options for [`get_if_addrs_with_options`].
+///
+/// The defaults return the same interfaces as [`get_if_addrs`](crate::get_if_addrs).
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone)]
+pub struct IfAddrsOptions {
+    exclude_link_local: bool,
+    sorted: bool,
+    skip_index: bool,
+    skip_status: bool,
+    system_configuration: bool,
+    link_details: bool,
+}
+
+impl IfAddrsOptions {
+    /// Create the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drop IPv4 link-local (`169.254.0.0/16`) addresses. The operating system backends never
+    /// report IPv6 link-local (`fe80::/10`) addresses; this also drops those a registered
+    /// [`IfAddrsSource`](crate::IfAddrsSource) or an `env-override` file supplies.
+    pub fn exclude_link_local(mut self, exclude: bool) -> Self {
+        self.exclude_link_local = exclude;
+        self
+    }
+
+    /// Sort the results by interface index, then IPv4 before IPv6, then address, so that
+    /// identical configurations compare and hash equal across calls. Interfaces without a known
+    /// index come last, ordered by name.
+    ///
+    /// Without this the order is whatever the operating system reports, which may change
+    /// between calls.
+    pub fn sorted(mut self, sorted: bool) -> Self {
+        self.sorted = sorted;
+        self
+    }
+
+    /// Skip looking up the interface index, leaving [`Interface::index`] `None`. Where the
+    /// index does not come with the enumeration itself, as with getifaddrs, this saves a
+    /// system call per address.
+    pub fn skip_index(mut self, skip: bool) -> Self {
+        self.skip_index = skip;
+        self
+    }
+
+    /// Skip determining whether links have a carrier, leaving [`Interface::has_carrier`]
+    /// `None`. This saves a query per adapter on Windows, and reading it from sysfs once per
+    /// link on Linux when [`link_details`](IfAddrsOptions::link_details) are read.
+    pub fn skip_status(mut self, skip: bool) -> Self {
+        self.skip_status = skip;
+        self
+    }
+
+    /// Look up the SystemConfiguration type, localized display name and hardware address of
+    /// interfaces on macOS, filling [`Interface::interface_type`], [`Interface::display_name`]
+    /// and [`Interface::adapter_id`]. This asks `configd` over IPC on every call, so it is off
+    /// by default. It has no effect on other platforms or with the `lean` feature.
+    pub fn system_configuration(mut self, lookup: bool) -> Self {
+        self.system_configuration = lookup;
+        self
+    }
+
+    /// Read the details of each link from sysfs on Linux: the kind of device behind
+    /// [`Interface::is_virtual`], [`is_vpn_like`](Interface::is_vpn_like),
+    /// [`is_wireless`](Interface::is_wireless) and [`is_cellular`](Interface::is_cellular),
+    /// along with [`Interface::adapter_id`] and [`Interface::duplex`]. With getifaddrs this
+    /// also fills [`Interface::alias`], [`Interface::txqueuelen`], [`Interface::group`] and
+    /// [`Interface::has_carrier`], and adds `IFF_PROMISC` to the flags while a packet capture
+    /// holds the device; the netlink backend of the `rustix` feature reports those anyway.
+    ///
+    /// This takes about a dozen file reads per link, so it is off by default. It has no effect
+    /// on other platforms or with the `lean` feature.
+    pub fn link_details(mut self, read: bool) -> Self {
+        self.link_details = read;
+        self
+    }
+
+    fn lookups(&self) -> Lookups {
+        Lookups {
+            index: !self.skip_index,
+            status: !self.skip_status,
+            system_configuration: self.system_configuration,
+            link_details: self.link_details,
+        }
+    }
+
+    fn keep(&self, iface: &Interface) -> bool {
+        !(self.exclude_link_local && crate::is_link_local(iface.ip()))
+    }
+}
+
+/// The optional per-interface lookups the backends make while enumerating.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub(crate) struct Lookups {
+    /// Look up the interface index.
+    pub index: bool,
+    /// Determine whether the link has a carrier.
+    pub status: bool,
+    /// Ask SystemConfiguration for the type and display name of interfaces on macOS.
+    pub system_configuration: bool,
+    /// Read the details of each link from sysfs on Linux.
+    pub link_details: bool,
+}
+
+impl Lookups {
+    /// The lookups [`get_if_addrs`](crate::get_if_addrs) makes.
+    pub const DEFAULT: Self = Self {
+        index: true,
+        status: true,
+        system_configuration: false,
+        link_details: false,
+    };
+
+    /// Clear the details of `iface` that were not asked for, as some backends and custom
+    /// sources report them regardless.
+    pub fn apply(self, iface: &mut Interface) {
+        if !self.index {
+            iface.index = None;
+        }
+        if !self.status {
+            iface.has_carrier = None;
+        }
+    }
+}
+
+fn sort_key(iface: &Interface) -> (bool, Option<u32>, &str, IpAddr) {
+    (iface.index.is_none(), iface.index, &iface.name, iface.ip())
+}
+
+/// Get the network interfaces on this machine like [`get_if_addrs`](crate::get_if_addrs),
+/// narrowed down by `options`.
+pub fn get_if_addrs_with_options(options: &IfAddrsOptions) -> io::Result<Vec<Interface>> {
+    let mut ret = crate::source::get_if_addrs_with(options.lookups())?;
+    ret.retain(|iface| options.keep(iface));
+    if options.sorted {
+        ret.sort_by(|a, b| sort_key(a).cmp(&sort_key(b)));
+    }
+    Ok(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Lookups;
+    use crate::{IfAddr, Ifv4Addr, Interface};
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn test_lookups_apply() {
+        let addr = IfAddr::V4(Ifv4Addr {
+            ip: Ipv4Addr::new(192, 0, 2, 1),
+            netmask: Ipv4Addr::new(255, 255, 255, 0),
+            broadcast: None,
+        });
+        let mut iface = Interface::new("eth0".to_string(), addr, Some(2));
+        iface.has_carrier = Some(true);
+
+        Lookups::DEFAULT.apply(&mut iface);
+        assert_eq!((iface.index, iface.has_carrier), (Some(2), Some(true)));
+        let lookups = Lookups {
+            status: false,
+            ..Lookups::DEFAULT
+        };
+        lookups.apply(&mut iface);
+        assert_eq!((iface.index, iface.has_carrier), (Some(2), None));
+        let lookups = Lookups {
+            index: false,
+            ..Lookups::DEFAULT
+        };
+        lookups.apply(&mut iface);
+        assert_eq!(iface.index, None);
+    }
+}
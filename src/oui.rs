@@ -0,0 +1,98 @@
+// Copyright 2018 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! A compact table of organizationally unique identifiers (OUIs), the first three bytes of a
+//! MAC address assigned by the IEEE. It covers common hardware and hypervisor vendors rather
+//! than the whole registry.
+
+/// `(OUI, vendor)` pairs, sorted by OUI for binary search.
+static OUIS: &[([u8; 3], &str)] = &[
+    ([0x00, 0x00, 0x0C], "Cisco"),
+    ([0x00, 0x00, 0x5E], "IANA"),
+    ([0x00, 0x00, 0xF0], "Samsung"),
+    ([0x00, 0x01, 0xE8], "Force10 Networks"),
+    ([0x00, 0x02, 0xB3], "Intel"),
+    ([0x00, 0x03, 0x93], "Apple"),
+    ([0x00, 0x03, 0xFF], "Microsoft"),
+    ([0x00, 0x04, 0x23], "Intel"),
+    ([0x00, 0x04, 0x96], "Extreme Networks"),
+    ([0x00, 0x05, 0x69], "VMware"),
+    ([0x00, 0x05, 0x85], "Juniper Networks"),
+    ([0x00, 0x06, 0x5B], "Dell"),
+    ([0x00, 0x07, 0xE9], "Intel"),
+    ([0x00, 0x08, 0x74], "Dell"),
+    ([0x00, 0x09, 0x0F], "Fortinet"),
+    ([0x00, 0x0A, 0x95], "Apple"),
+    ([0x00, 0x0B, 0xDB], "Dell"),
+    ([0x00, 0x0C, 0x29], "VMware"),
+    ([0x00, 0x0C, 0x6E], "ASUSTek"),
+    ([0x00, 0x0D, 0x3A], "Microsoft"),
+    ([0x00, 0x0E, 0x0C], "Intel"),
+    ([0x00, 0x0E, 0xC6], "ASIX Electronics"),
+    ([0x00, 0x10, 0x18], "Broadcom"),
+    ([0x00, 0x10, 0xDB], "Juniper Networks"),
+    ([0x00, 0x11, 0x2F], "ASUSTek"),
+    ([0x00, 0x11, 0x32], "Synology"),
+    ([0x00, 0x13, 0x20], "Intel"),
+    ([0x00, 0x14, 0x22], "Dell"),
+    ([0x00, 0x15, 0x5D], "Microsoft"),
+    ([0x00, 0x16, 0x3E], "Xensource"),
+    ([0x00, 0x17, 0xF2], "Apple"),
+    ([0x00, 0x1A, 0x11], "Google"),
+    ([0x00, 0x1B, 0x17], "Palo Alto Networks"),
+    ([0x00, 0x1B, 0x21], "Intel"),
+    ([0x00, 0x1C, 0x14], "VMware"),
+    ([0x00, 0x1C, 0x42], "Parallels"),
+    ([0x00, 0x1C, 0x73], "Arista Networks"),
+    ([0x00, 0x1E, 0xC2], "Apple"),
+    ([0x00, 0x25, 0x00], "Apple"),
+    ([0x00, 0x25, 0x90], "Super Micro Computer"),
+    ([0x00, 0x26, 0xBB], "Apple"),
+    ([0x00, 0x40, 0x96], "Cisco"),
+    ([0x00, 0x50, 0x56], "VMware"),
+    ([0x00, 0x50, 0xF2], "Microsoft"),
+    ([0x00, 0x60, 0x2F], "Cisco"),
+    ([0x00, 0x90, 0x27], "Intel"),
+    ([0x00, 0xA0, 0xC9], "Intel"),
+    ([0x00, 0xAA, 0x00], "Intel"),
+    ([0x00, 0xE0, 0x4C], "Realtek"),
+    ([0x08, 0x00, 0x27], "PCS Systemtechnik"),
+    ([0x28, 0xCD, 0xC1], "Raspberry Pi Trading"),
+    ([0x3C, 0x5A, 0xB4], "Google"),
+    ([0xB8, 0x27, 0xEB], "Raspberry Pi Foundation"),
+    ([0xD8, 0x3A, 0xDD], "Raspberry Pi Trading"),
+    ([0xDC, 0xA6, 0x32], "Raspberry Pi Trading"),
+    ([0xE4, 0x5F, 0x01], "Raspberry Pi Trading"),
+    ([0xF0, 0x18, 0x98], "Apple"),
+];
+
+/// Look up the vendor the OUI `oui` is assigned to.
+pub fn vendor(oui: [u8; 3]) -> Option<&'static str> {
+    OUIS.binary_search_by(|&(key, _)| key.cmp(&oui))
+        .ok()
+        .map(|i| OUIS[i].1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OUIS;
+    use crate::MacAddr;
+
+    #[test]
+    fn test_vendor() {
+        assert!(OUIS.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        let mac = MacAddr::new([0x00, 0x50, 0x56, 0x12, 0x34, 0x56]);
+        assert_eq!(mac.vendor(), Some("VMware"));
+        // The same bytes with the locally administered bit set.
+        let mac = MacAddr::new([0x02, 0x50, 0x56, 0x12, 0x34, 0x56]);
+        assert_eq!(mac.vendor(), None);
+        let mac = MacAddr::new([0x00, 0x00, 0x01, 0x12, 0x34, 0x56]);
+        assert_eq!(mac.vendor(), None);
+    }
+}
@@ -12,11 +12,13 @@ use crate::sockaddr;
 use if_addrs_sys::{freeifaddrs, getifaddrs, ifaddrs};
 #[cfg(not(target_os = "android"))]
 use libc::{freeifaddrs, getifaddrs, ifaddrs};
+use std::ffi::CStr;
+use std::marker::PhantomData;
 use std::net::IpAddr;
 use std::{io, mem};
 
-#[cfg(any(target_os = "linux", target_os = "android", target_os = "nacl"))]
-pub fn do_broadcast(ifaddr: &ifaddrs) -> Option<IpAddr> {
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn do_broadcast(ifaddr: &ifaddrs) -> Option<IpAddr> {
     sockaddr::to_ipaddr(ifaddr.ifa_ifu)
 }
 
@@ -27,7 +29,7 @@ pub fn do_broadcast(ifaddr: &ifaddrs) -> Option<IpAddr> {
     target_os = "openbsd",
     target_os = "netbsd"
 ))]
-pub fn do_broadcast(ifaddr: &ifaddrs) -> Option<IpAddr> {
+fn do_broadcast(ifaddr: &ifaddrs) -> Option<IpAddr> {
     sockaddr::to_ipaddr(ifaddr.ifa_dstaddr)
 }
 
@@ -50,8 +52,11 @@ impl IfAddrs {
         }
     }
 
-    pub fn iter(&self) -> IfAddrsIterator {
-        IfAddrsIterator { next: self.inner }
+    pub fn iter(&self) -> IfAddrsIterator<'_> {
+        IfAddrsIterator {
+            next: self.inner,
+            marker: PhantomData,
+        }
     }
 }
 
@@ -64,24 +69,142 @@ impl Drop for IfAddrs {
     }
 }
 
-pub struct IfAddrsIterator {
-    next: *mut ifaddrs,
+/// An entry of the `getifaddrs` list, borrowed from the `IfAddrs` that owns it.
+#[derive(Clone, Copy)]
+pub struct IfAddrsEntry<'a> {
+    inner: &'a ifaddrs,
 }
 
-impl Iterator for IfAddrsIterator {
-    type Item = ifaddrs;
+impl<'a> IfAddrsEntry<'a> {
+    /// The name of the interface.
+    #[allow(unsafe_code)]
+    pub fn name(&self) -> &'a CStr {
+        unsafe { CStr::from_ptr(self.inner.ifa_name) }
+    }
+
+    /// The `IFF_*` flags of the interface.
+    pub fn flags(&self) -> u32 {
+        self.inner.ifa_flags
+    }
+
+    /// The IP address, unless the entry has another family, is cut short or is skipped by
+    /// `sockaddr::to_ipaddr`.
+    pub fn addr(&self) -> Option<IpAddr> {
+        if self.addr_is_truncated() {
+            return None;
+        }
+        sockaddr::to_ipaddr(self.inner.ifa_addr)
+    }
+
+    /// Whether the entry has an IPv4 or IPv6 address that is cut short, which `addr` skips.
+    pub fn addr_is_truncated(&self) -> bool {
+        sockaddr::is_truncated(self.inner.ifa_addr)
+    }
+
+    /// The netmask of the IP address.
+    pub fn netmask(&self) -> Option<IpAddr> {
+        sockaddr::to_netmask(self.inner.ifa_netmask, self.addr()?)
+    }
+
+    /// The broadcast address, if the interface has `IFF_BROADCAST` set.
+    pub fn broadcast(&self) -> Option<IpAddr> {
+        if self.flags() & libc::IFF_BROADCAST as u32 != 0 {
+            do_broadcast(self.inner)
+        } else {
+            None
+        }
+    }
+
+    /// The 6-byte hardware address and interface index of a link-layer entry.
+    pub fn hwaddr(&self) -> Option<([u8; 6], u32)> {
+        sockaddr::to_hwaddr(self.inner.ifa_addr)
+    }
+}
+
+pub struct IfAddrsIterator<'a> {
+    next: *const ifaddrs,
+    marker: PhantomData<&'a IfAddrs>,
+}
+
+impl<'a> Iterator for IfAddrsIterator<'a> {
+    type Item = IfAddrsEntry<'a>;
 
     #[allow(unsafe_code)]
     fn next(&mut self) -> Option<Self::Item> {
-        if self.next.is_null() {
-            return None;
-        };
+        // The list lives until the `IfAddrs` this iterator borrows is dropped.
+        let inner: &'a ifaddrs = unsafe { self.next.as_ref()? };
+        self.next = inner.ifa_next;
+        Some(IfAddrsEntry { inner })
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::IfAddrsIterator;
+    use libc::{ifaddrs, sockaddr, sockaddr_in, AF_INET, AF_UNSPEC, IFF_BROADCAST, IFF_UP};
+    use std::marker::PhantomData;
+    use std::net::{IpAddr, Ipv4Addr};
+    use std::{mem, ptr};
+
+    #[allow(unsafe_code)]
+    fn sockaddr_in(family: i32, ip: Ipv4Addr) -> sockaddr_in {
+        let mut sin: sockaddr_in = unsafe { mem::zeroed() };
+        sin.sin_family = family as _;
+        sin.sin_addr.s_addr = u32::from(ip).to_be();
+        sin
+    }
 
-        Some(unsafe {
-            let result = *self.next;
-            self.next = (*self.next).ifa_next;
+    #[test]
+    #[allow(unsafe_code)]
+    fn test_entries() {
+        let addr = sockaddr_in(AF_INET, Ipv4Addr::new(192, 0, 2, 1));
+        let netmask = sockaddr_in(AF_UNSPEC, Ipv4Addr::new(255, 255, 255, 0));
+        let broadcast = sockaddr_in(AF_INET, Ipv4Addr::new(192, 0, 2, 255));
+        let as_ptr = |sin: &sockaddr_in| sin as *const sockaddr_in as *mut sockaddr;
 
-            result
-        })
+        let mut second: ifaddrs = unsafe { mem::zeroed() };
+        second.ifa_name = b"eth0:1\0".as_ptr() as *mut _;
+        second.ifa_flags = IFF_UP as u32;
+        second.ifa_addr = as_ptr(&addr);
+        second.ifa_ifu = as_ptr(&broadcast);
+        let mut first = second;
+        first.ifa_name = b"eth0\0".as_ptr() as *mut _;
+        first.ifa_flags = (IFF_UP | IFF_BROADCAST) as u32;
+        first.ifa_netmask = as_ptr(&netmask);
+        first.ifa_next = &mut second;
+
+        let entries: Vec<_> = IfAddrsIterator {
+            next: &first,
+            marker: PhantomData,
+        }
+        .collect();
+        assert_eq!(entries.len(), 2);
+
+        let ip = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+        assert_eq!(entries[0].name().to_str(), Ok("eth0"));
+        assert_eq!(entries[0].addr(), Some(ip));
+        // The netmask is read in the family of the address.
+        assert_eq!(
+            entries[0].netmask(),
+            Some(IpAddr::V4(Ipv4Addr::new(255, 255, 255, 0)))
+        );
+        assert_eq!(
+            entries[0].broadcast(),
+            Some(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 255)))
+        );
+        assert_eq!(entries[0].hwaddr(), None);
+
+        // No netmask, and the broadcast address only counts with `IFF_BROADCAST`.
+        assert_eq!(entries[1].name().to_str(), Ok("eth0:1"));
+        assert_eq!(entries[1].addr(), Some(ip));
+        assert_eq!(entries[1].netmask(), None);
+        assert_eq!(entries[1].broadcast(), None);
+        assert!(entries.iter().all(|entry| !entry.addr_is_truncated()));
+
+        let empty = IfAddrsIterator {
+            next: ptr::null(),
+            marker: PhantomData,
+        };
+        assert_eq!(empty.count(), 0);
     }
 }
@@ -0,0 +1,474 @@
+// Copyright 2018 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Enumeration from `/proc/net` and `/sys/class/net`, for sandboxes where getifaddrs or
+//! netlink sockets are blocked but the filesystem is still readable.
+//!
+//! IPv6 addresses come straight from `/proc/net/if_inet6`. The kernel has no such listing for
+//! IPv4, so local addresses are taken from `/proc/net/fib_trie` and attributed to the interface
+//! of the most specific on-link route in `/proc/net/route` that covers them.
+
+use crate::options::Lookups;
+use crate::{Duplex, IfAddr, Ifv4Addr, Ifv6Addr, Interface};
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::Path;
+
+const IFF_BROADCAST: u32 = 0x2;
+const RTF_UP: u32 = 0x1;
+const RTF_GATEWAY: u32 = 0x2;
+
+/// Fall back to `/proc` when the primary backend failed to fill `ret`, keeping the original
+/// error if that fails as well.
+pub fn or_fallback(
+    result: io::Result<()>,
+    ret: &mut Vec<Interface>,
+    lookups: Lookups,
+) -> io::Result<()> {
+    result.or_else(|err| {
+        ret.extend(get_if_addrs(lookups).map_err(|_| err)?);
+        Ok(())
+    })
+}
+
+/// Return a vector of IP details for all the valid interfaces on this host, making the
+/// optional `lookups`.
+pub fn get_if_addrs(lookups: Lookups) -> io::Result<Vec<Interface>> {
+    let mut ret = Vec::new();
+
+    let fib_trie = fs::read_to_string(proc_net("fib_trie"))?;
+    let (locals, broadcasts) = parse_fib_trie(&fib_trie);
+    let routes = parse_route(&fs::read_to_string(proc_net("route"))?);
+    let mut links = LinkCache::new(lookups);
+    for ip in locals {
+        let (name, prefix_len) = if ip.is_loopback() {
+            ("lo".to_string(), 8)
+        } else {
+            match routes
+                .iter()
+                .filter(|&&(_, dest, len)| prefix_matches(ip, dest, len))
+                .max_by_key(|&&(_, _, len)| len)
+            {
+                Some(&(ref name, _, len)) => (name.clone(), len),
+                None => continue,
+            }
+        };
+        let link = links.get(&name);
+        let mask = u32::MAX.checked_shl(32 - prefix_len).unwrap_or(0);
+        let broadcast = Ipv4Addr::from(u32::from(ip) | !mask);
+        let broadcast =
+            if link.flags.unwrap_or(0) & IFF_BROADCAST != 0 && broadcasts.contains(&broadcast) {
+                Some(broadcast)
+            } else {
+                None
+            };
+        let mut iface = Interface::new(
+            name,
+            IfAddr::V4(Ifv4Addr {
+                ip,
+                netmask: Ipv4Addr::from(mask),
+                broadcast,
+            }),
+            link.index,
+        );
+        link.apply(&mut iface);
+        ret.push(iface);
+    }
+
+    // address ifindex prefixlen scope flags name, all but the name in hex.
+    for line in fs::read_to_string(proc_net("if_inet6"))?.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 6 {
+            continue;
+        }
+        let (ip, index, prefix_len) = match (
+            u128::from_str_radix(fields[0], 16),
+            u32::from_str_radix(fields[1], 16),
+            u32::from_str_radix(fields[2], 16),
+        ) {
+            (Ok(ip), Ok(index), Ok(prefix_len)) if prefix_len <= 128 => (ip, index, prefix_len),
+            _ => continue,
+        };
+        let ip = Ipv6Addr::from(ip);
+        // Skip link-local addresses like the other backends.
+        if crate::is_link_local(IpAddr::V6(ip)) {
+            continue;
+        }
+        let mut iface = Interface::new(
+            fields[5].to_string(),
+            IfAddr::V6(Ifv6Addr {
+                ip,
+                netmask: Ipv6Addr::from(u128::MAX.checked_shl(128 - prefix_len).unwrap_or(0)),
+                broadcast: None,
+            }),
+            Some(index).filter(|_| lookups.index),
+        );
+        links.get(&iface.name).apply(&mut iface);
+        ret.push(iface);
+    }
+
+    Ok(ret)
+}
+
+/// Count the devices listed in `/proc/net/dev`, after its two header lines.
+pub fn interface_count() -> io::Result<usize> {
+    Ok(fs::read_to_string(proc_net("dev"))?.lines().skip(2).count())
+}
+
+/// The names of the devices listed in `/proc/net/dev`, each followed by a colon.
+#[cfg(target_os = "android")]
+pub fn interface_names() -> io::Result<Vec<String>> {
+    Ok(fs::read_to_string(proc_net("dev"))?
+        .lines()
+        .skip(2)
+        .filter_map(|line| line.split(':').next())
+        .map(|name| name.trim().to_string())
+        .collect())
+}
+
+/// Collect the local and broadcast addresses listed in `/proc/net/fib_trie`.
+///
+/// Every leaf is a `|-- address` line followed by one line per route type, such as
+/// `/32 host LOCAL` for the addresses assigned to this host.
+fn parse_fib_trie(fib_trie: &str) -> (Vec<Ipv4Addr>, Vec<Ipv4Addr>) {
+    let mut locals = Vec::new();
+    let mut broadcasts = Vec::new();
+    let mut leaf = None;
+    for line in fib_trie.lines() {
+        let line = line.trim();
+        if let Some(addr) = line.strip_prefix("|-- ") {
+            leaf = addr.parse::<Ipv4Addr>().ok();
+        } else if let Some(ip) = leaf {
+            let list = match line {
+                "/32 host LOCAL" => &mut locals,
+                "/32 link BROADCAST" => &mut broadcasts,
+                _ => continue,
+            };
+            if !list.contains(&ip) {
+                list.push(ip);
+            }
+        }
+    }
+    (locals, broadcasts)
+}
+
+/// Parse the on-link routes of `/proc/net/route` into `(name, destination, prefix_len)`.
+fn parse_route(route: &str) -> Vec<(String, Ipv4Addr, u32)> {
+    route
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 8 {
+                return None;
+            }
+            let destination = u32::from_str_radix(fields[1], 16).ok()?;
+            let flags = u32::from_str_radix(fields[3], 16).ok()?;
+            let mask = u32::from_str_radix(fields[7], 16).ok()?;
+            if flags & RTF_UP == 0 || flags & RTF_GATEWAY != 0 {
+                return None;
+            }
+            Some((
+                fields[0].to_string(),
+                Ipv4Addr::from(destination.to_ne_bytes()),
+                mask.count_ones(),
+            ))
+        })
+        .collect()
+}
+
+fn prefix_matches(ip: Ipv4Addr, destination: Ipv4Addr, prefix_len: u32) -> bool {
+    let mask = u32::MAX.checked_shl(32 - prefix_len).unwrap_or(0);
+    u32::from(ip) & mask == u32::from(destination) & mask
+}
+
+thread_local! {
+    /// Set on threads that switched to another network namespace. `/proc/net` and sysfs keep
+    /// showing the namespace of the process there, so the thread has to use
+    /// `/proc/thread-self/net` and cannot consult sysfs at all.
+    static FOREIGN_NETNS: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Mark the current thread as having switched network namespaces.
+#[cfg(target_os = "linux")]
+pub fn set_foreign_netns() {
+    FOREIGN_NETNS.with(|foreign| foreign.set(true));
+}
+
+fn foreign_netns() -> bool {
+    FOREIGN_NETNS.with(Cell::get)
+}
+
+fn proc_net(file: &str) -> String {
+    if foreign_netns() {
+        format!("/proc/thread-self/net/{}", file)
+    } else {
+        format!("/proc/net/{}", file)
+    }
+}
+
+/// What sysfs tells about one link.
+#[derive(Default)]
+pub struct LinkDetails {
+    pub index: Option<u32>,
+    pub flags: Option<u32>,
+    pub alias: Option<String>,
+    pub txqueuelen: Option<u32>,
+    pub group: Option<u32>,
+    pub virtual_device: Option<bool>,
+    pub vpn_device: Option<bool>,
+    pub wireless_device: Option<bool>,
+    pub cellular_device: Option<bool>,
+    pub adapter_id: Option<String>,
+    pub carrier: Option<bool>,
+    pub duplex: Option<Duplex>,
+}
+
+impl LinkDetails {
+    /// Read the flags of the link `name`, and its index and other details only if `lookups`
+    /// asks for them.
+    fn read(name: &str, lookups: Lookups) -> Self {
+        let mut link = Self {
+            index: if lookups.index {
+                sysfs(name, "ifindex").and_then(|index| index.parse().ok())
+            } else {
+                None
+            },
+            flags: sysfs(name, "flags").and_then(|flags| parse_flags(&flags)),
+            ..Self::default()
+        };
+        if !lookups.link_details {
+            return link;
+        }
+        link.alias = alias(name);
+        link.txqueuelen = sysfs(name, "tx_queue_len").and_then(|len| len.parse().ok());
+        link.group = sysfs(name, "netdev_group").and_then(|group| group.parse().ok());
+        link.virtual_device = is_virtual(name);
+        link.vpn_device = is_vpn_like(name);
+        link.wireless_device = is_wireless(name);
+        link.cellular_device = is_cellular(name);
+        link.adapter_id = adapter_id(name);
+        if lookups.status {
+            link.carrier = carrier(name);
+        }
+        link.duplex = duplex(name);
+        link
+    }
+
+    /// Copy the details onto `iface`, except for its index.
+    pub fn apply(&self, iface: &mut Interface) {
+        iface.flags = self.flags;
+        iface.has_carrier = self.carrier;
+        iface.alias = self.alias.clone();
+        iface.txqueuelen = self.txqueuelen;
+        iface.group = self.group;
+        self.apply_device(iface);
+    }
+
+    /// Copy the kind of device and its hardware onto `iface`, which neither getifaddrs nor
+    /// netlink report.
+    pub fn apply_device(&self, iface: &mut Interface) {
+        iface.virtual_device = self.virtual_device;
+        iface.vpn_device = self.vpn_device;
+        iface.wireless_device = self.wireless_device;
+        iface.cellular_device = self.cellular_device;
+        iface.adapter_id = self.adapter_id.clone();
+        iface.duplex = self.duplex;
+    }
+}
+
+/// The links read from sysfs during one enumeration, so that each is read once however many
+/// addresses it has. Alias labels such as `eth0:1` share the details of their link.
+pub struct LinkCache {
+    lookups: Lookups,
+    links: HashMap<String, LinkDetails>,
+}
+
+impl LinkCache {
+    pub fn new(lookups: Lookups) -> Self {
+        Self {
+            lookups,
+            links: HashMap::new(),
+        }
+    }
+
+    pub fn get(&mut self, name: &str) -> &LinkDetails {
+        let link = crate::link_name(name);
+        if !self.links.contains_key(link) {
+            let details = LinkDetails::read(link, self.lookups);
+            self.links.insert(link.to_string(), details);
+        }
+        &self.links[link]
+    }
+}
+
+fn sysfs(name: &str, attr: &str) -> Option<String> {
+    if foreign_netns() {
+        return None;
+    }
+    fs::read_to_string(format!("/sys/class/net/{}/{}", name, attr))
+        .ok()
+        .map(|value| value.trim().to_string())
+}
+
+/// The alias of an interface, which sysfs reports as an empty string when unset.
+fn alias(name: &str) -> Option<String> {
+    sysfs(name, "ifalias").filter(|alias| !alias.is_empty())
+}
+
+/// Whether the device is a software one, which sysfs places under `/sys/devices/virtual`.
+fn is_virtual(name: &str) -> Option<bool> {
+    if foreign_netns() {
+        return None;
+    }
+    let path = fs::canonicalize(format!("/sys/class/net/{}", name)).ok()?;
+    Some(path.starts_with("/sys/devices/virtual"))
+}
+
+/// Whether the device is a tun/tap, PPP or WireGuard tunnel.
+fn is_vpn_like(name: &str) -> Option<bool> {
+    const ARPHRD_PPP: &str = "512";
+    let dev_type = sysfs(name, "type")?;
+    Some(
+        dev_type == ARPHRD_PPP
+            || Path::new(&format!("/sys/class/net/{}/tun_flags", name)).exists()
+            || sysfs(name, "uevent")
+                .unwrap_or_default()
+                .lines()
+                .any(|line| line == "DEVTYPE=wireguard"),
+    )
+}
+
+/// Whether the device is a WiFi one: cfg80211 links it to its `phy80211`, and drivers with
+/// wireless extensions add a `wireless` directory.
+fn is_wireless(name: &str) -> Option<bool> {
+    if foreign_netns() {
+        return None;
+    }
+    let dir = format!("/sys/class/net/{}", name);
+    if !Path::new(&dir).exists() {
+        return None;
+    }
+    Some(Path::new(&dir).join("phy80211").exists() || Path::new(&dir).join("wireless").exists())
+}
+
+/// The sysfs path of the hardware behind the device, in the `/devices/...` form udev calls
+/// `DEVPATH`, e.g. `/devices/pci0000:00/0000:00:1f.6`. Software devices have none.
+fn adapter_id(name: &str) -> Option<String> {
+    if foreign_netns() {
+        return None;
+    }
+    let path = fs::canonicalize(format!("/sys/class/net/{}/device", name)).ok()?;
+    let path = path.strip_prefix("/sys").ok()?;
+    Some(format!("/{}", path.to_string_lossy()))
+}
+
+/// Whether the device is a cellular modem: a WWAN device such as `qmi_wwan`, or a raw IP one
+/// like Qualcomm's `rmnet`.
+fn is_cellular(name: &str) -> Option<bool> {
+    const ARPHRD_RAWIP: &str = "519";
+    let dev_type = sysfs(name, "type")?;
+    Some(
+        dev_type == ARPHRD_RAWIP
+            || sysfs(name, "uevent")
+                .unwrap_or_default()
+                .lines()
+                .any(|line| line == "DEVTYPE=wwan"),
+    )
+}
+
+/// Whether the device has a carrier. sysfs refuses to report it while the device is down.
+fn carrier(name: &str) -> Option<bool> {
+    match sysfs(name, "carrier")?.as_str() {
+        "0" => Some(false),
+        "1" => Some(true),
+        _ => None,
+    }
+}
+
+/// The duplex mode ethtool negotiated, which sysfs shows as `unknown` for links that are down
+/// and fails to read for devices without ethtool link settings.
+fn duplex(name: &str) -> Option<Duplex> {
+    match sysfs(name, "duplex")?.as_str() {
+        "full" => Some(Duplex::Full),
+        "half" => Some(Duplex::Half),
+        _ => None,
+    }
+}
+
+fn parse_flags(flags: &str) -> Option<u32> {
+    u32::from_str_radix(flags.trim_start_matches("0x"), 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_fib_trie, LinkDetails};
+    use crate::options::Lookups;
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn test_link_details_lookups() {
+        // Only the flags unless the details are asked for.
+        let link = LinkDetails::read("lo", Lookups::DEFAULT);
+        assert!(link.flags.is_some());
+        assert_eq!((link.txqueuelen, link.virtual_device), (None, None));
+
+        let lookups = Lookups {
+            index: false,
+            link_details: true,
+            ..Lookups::DEFAULT
+        };
+        let link = LinkDetails::read("lo", lookups);
+        assert_eq!((link.index, link.virtual_device), (None, Some(true)));
+        assert!(link.txqueuelen.is_some());
+    }
+
+    #[test]
+    fn test_parse_fib_trie() {
+        let fib_trie = "\
+Main:
+  +-- 0.0.0.0/0 3 0 5
+     +-- 127.0.0.0/8 2 0 2
+        +-- 127.0.0.0/31 1 0 0
+           |-- 127.0.0.0
+              /8 host LOCAL
+           |-- 127.0.0.1
+              /32 host LOCAL
+        |-- 127.255.255.255
+           /32 link BROADCAST
+     +-- 192.0.2.0/24 2 0 2
+        +-- 192.0.2.0/30 2 0 2
+           |-- 192.0.2.0
+              /24 link UNICAST
+           |-- 192.0.2.2
+              /32 host LOCAL
+        |-- 192.0.2.255
+           /32 link BROADCAST
+Local:
+  +-- 0.0.0.0/0 3 0 5
+           |-- 192.0.2.2
+              /32 host LOCAL
+";
+        let (locals, broadcasts) = parse_fib_trie(fib_trie);
+        assert_eq!(
+            locals,
+            vec![Ipv4Addr::new(127, 0, 0, 1), Ipv4Addr::new(192, 0, 2, 2)]
+        );
+        assert_eq!(
+            broadcasts,
+            vec![
+                Ipv4Addr::new(127, 255, 255, 255),
+                Ipv4Addr::new(192, 0, 2, 255)
+            ]
+        );
+    }
+}
@@ -0,0 +1,450 @@
+// Copyright 2018 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Default address selection for IPv6, as described in RFC 6724.
+//!
+//! Only the rules that can be decided from the interface table are applied: there is no
+//! information about deprecated, home, temporary or tunnelled addresses, or about routes, so
+//! the rules that need it are skipped. Addresses of interfaces that are not
+//! up (see [`Interface::is_up`]) are never selected as sources.
+
+use crate::{IfAddr, Interface};
+use alloc::vec::Vec;
+use core::cmp::{Ordering, Reverse};
+#[cfg(not(feature = "std"))]
+use core::net::{IpAddr, Ipv6Addr};
+#[cfg(feature = "std")]
+use std::io;
+#[cfg(feature = "std")]
+use std::net::{IpAddr, Ipv6Addr};
+
+const SCOPE_LINK_LOCAL: u8 = 0x2;
+const SCOPE_SITE_LOCAL: u8 = 0x5;
+const SCOPE_GLOBAL: u8 = 0xe;
+
+/// The default policy table of RFC 6724 section 2.1, as (prefix, prefix length, precedence,
+/// label), longest prefixes first.
+const POLICY_TABLE: [(u128, u32, u8, u8); 9] = [
+    (0x0000_0000_0000_0000_0000_0000_0000_0001, 128, 50, 0),
+    (0x0000_0000_0000_0000_0000_ffff_0000_0000, 96, 35, 4),
+    (0x0000_0000_0000_0000_0000_0000_0000_0000, 96, 1, 3),
+    (0x2001_0000_0000_0000_0000_0000_0000_0000, 32, 5, 5),
+    (0x2002_0000_0000_0000_0000_0000_0000_0000, 16, 30, 2),
+    (0x3ffe_0000_0000_0000_0000_0000_0000_0000, 16, 1, 12),
+    (0xfec0_0000_0000_0000_0000_0000_0000_0000, 10, 1, 11),
+    (0xfc00_0000_0000_0000_0000_0000_0000_0000, 7, 3, 13),
+    (0x0000_0000_0000_0000_0000_0000_0000_0000, 0, 40, 1),
+];
+
+/// Sort `dests` so that the most preferred destination comes first, following the destination
+/// address selection rules of RFC 6724 against the addresses configured on this host.
+///
+/// This is what a resolver should do with the addresses returned for a name before trying them
+/// in order. See [`sort_destination_addrs_with`] to supply the interface table yourself.
+#[cfg(feature = "std")]
+pub fn sort_destination_addrs(dests: &mut [IpAddr]) -> io::Result<()> {
+    let ifaces = crate::get_if_addrs()?;
+    sort_destination_addrs_with(dests, &ifaces);
+    Ok(())
+}
+
+/// Sort `dests` so that the most preferred destination comes first, following the destination
+/// address selection rules of RFC 6724 against the addresses configured on `ifaces`.
+///
+/// The sort is stable: destinations the rules cannot tell apart keep their relative order.
+pub fn sort_destination_addrs_with(dests: &mut [IpAddr], ifaces: &[Interface]) {
+    let sorted = sorted_with_sources(dests, ifaces);
+    for (dest, (candidate, _)) in dests.iter_mut().zip(sorted) {
+        *dest = candidate;
+    }
+}
+
+/// Pair each of `remotes` with the local interface to connect from, in the order connection
+/// attempts should be made, using this host's interfaces.
+///
+/// See [`connection_candidates_with`].
+#[cfg(feature = "std")]
+pub fn connection_candidates(remotes: &[IpAddr]) -> io::Result<Vec<(Interface, IpAddr)>> {
+    let ifaces = crate::get_if_addrs()?;
+    Ok(connection_candidates_with(remotes, &ifaces)
+        .into_iter()
+        .map(|(iface, remote)| (iface.clone(), remote))
+        .collect())
+}
+
+/// Pair each of `remotes` with the interface of `ifaces` to connect from, in the order
+/// connection attempts should be made.
+///
+/// Remotes are sorted per RFC 6724 and each is paired with the source address that RFC would
+/// select, so the local address always has a suitable scope. Only interfaces that are up are
+/// considered, and remotes without any usable local address are dropped. Deprecated and
+/// temporary addresses are not told apart from others, as the interface table does not say
+/// which addresses they are. Following Happy Eyeballs (RFC 8305, section 4), the result then
+/// alternates between address families, starting with the family of the most preferred remote.
+pub fn connection_candidates_with<'a>(
+    remotes: &[IpAddr],
+    ifaces: &'a [Interface],
+) -> Vec<(&'a Interface, IpAddr)> {
+    let (mut first, mut second): (Vec<_>, Vec<_>) = (Vec::new(), Vec::new());
+    let mut first_is_ipv4 = None;
+    for (remote, iface) in sorted_with_sources(remotes, ifaces) {
+        let iface = match iface {
+            Some(iface) => iface,
+            None => continue,
+        };
+        if *first_is_ipv4.get_or_insert(remote.is_ipv4()) == remote.is_ipv4() {
+            first.push((iface, remote));
+        } else {
+            second.push((iface, remote));
+        }
+    }
+
+    let mut ret = Vec::with_capacity(first.len() + second.len());
+    let (mut first, mut second) = (first.into_iter(), second.into_iter());
+    loop {
+        match (first.next(), second.next()) {
+            (None, None) => return ret,
+            (a, b) => ret.extend(a.into_iter().chain(b)),
+        }
+    }
+}
+
+/// Pair each of `dests` with its selected source and sort them by preference.
+fn sorted_with_sources<'a>(
+    dests: &[IpAddr],
+    ifaces: &'a [Interface],
+) -> Vec<(IpAddr, Option<&'a Interface>)> {
+    let mut candidates: Vec<(IpAddr, Option<&Interface>)> = dests
+        .iter()
+        .map(|&dest| (dest, select_source(dest, ifaces)))
+        .collect();
+    let rank_of =
+        |&(dest, source): &(IpAddr, Option<&Interface>)| rank(dest, source.map(|s| &s.addr));
+    candidates.sort_by_key(rank_of);
+
+    // Rule 9 only compares destinations of the same family, so applying it in the comparator
+    // would not give a total order. Instead, within each run of destinations the earlier rules
+    // cannot tell apart, sort each family among the positions it already holds, which leaves
+    // the interleaving of the families unchanged (rule 10).
+    let mut start = 0;
+    while start < candidates.len() {
+        let key = rank_of(&candidates[start]);
+        let len = candidates[start..]
+            .iter()
+            .take_while(|candidate| rank_of(candidate) == key)
+            .count();
+        let run = &mut candidates[start..start + len];
+        sort_by_prefix_len(run, true);
+        sort_by_prefix_len(run, false);
+        start += len;
+    }
+    candidates
+}
+
+/// Rule 9 of RFC 6724 section 6: stably sort the usable destinations of one family in `run`
+/// by the length of the prefix they share with their source, longest first. Destinations of
+/// the other family stay where they are.
+fn sort_by_prefix_len(run: &mut [(IpAddr, Option<&Interface>)], is_ipv4: bool) {
+    let positions: Vec<usize> = (0..run.len())
+        .filter(|&i| run[i].0.is_ipv4() == is_ipv4 && run[i].1.is_some())
+        .collect();
+    let mut members: Vec<_> = positions.iter().map(|&i| run[i]).collect();
+    members.sort_by_key(|&(dest, source)| {
+        Reverse(source.map_or(0, |source| common_prefix_len(&source.addr, dest)))
+    });
+    for (i, member) in positions.into_iter().zip(members) {
+        run[i] = member;
+    }
+}
+
+/// Pick the interface whose address RFC 6724 section 5 would use as the source to reach
+/// `dest`, if any.
+fn select_source(dest: IpAddr, ifaces: &[Interface]) -> Option<&Interface> {
+    let mut best: Option<&Interface> = None;
+    for iface in ifaces {
+        if iface.addr.ip().is_ipv4() != dest.is_ipv4() || !iface.is_up() {
+            continue;
+        }
+        best = match best {
+            Some(current)
+                if compare_sources(&current.addr, &iface.addr, dest) != Ordering::Greater =>
+            {
+                Some(current)
+            }
+            _ => Some(iface),
+        };
+    }
+    best
+}
+
+/// Order two candidate sources for `dest`; `Ordering::Less` means `sa` is preferred.
+fn compare_sources(sa: &IfAddr, sb: &IfAddr, dest: IpAddr) -> Ordering {
+    // Rule 1: prefer same address.
+    if sa.ip() == dest {
+        return Ordering::Less;
+    }
+    if sb.ip() == dest {
+        return Ordering::Greater;
+    }
+
+    // Rule 2: prefer appropriate scope.
+    let (scope_a, scope_b, scope_d) = (scope(sa.ip()), scope(sb.ip()), scope(dest));
+    if scope_a < scope_b {
+        return if scope_a < scope_d {
+            Ordering::Greater
+        } else {
+            Ordering::Less
+        };
+    }
+    if scope_b < scope_a {
+        return if scope_b < scope_d {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        };
+    }
+
+    // Rule 6: prefer matching label.
+    let label_d = policy(dest).1;
+    let (match_a, match_b) = (policy(sa.ip()).1 == label_d, policy(sb.ip()).1 == label_d);
+    if match_a != match_b {
+        return if match_a {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        };
+    }
+
+    // Rule 8: use longest matching prefix.
+    common_prefix_len(sb, dest).cmp(&common_prefix_len(sa, dest))
+}
+
+/// Rank a destination with its selected source by rules 1 to 8 of RFC 6724 section 6, which
+/// look at each destination on its own. Lower ranks are preferred.
+fn rank(dest: IpAddr, source: Option<&IfAddr>) -> (bool, bool, bool, Reverse<u8>, u8) {
+    let source = match source {
+        Some(source) => source,
+        // Rule 1: avoid unusable destinations.
+        None => return (true, false, false, Reverse(0), 0),
+    };
+    let (precedence, label) = policy(dest);
+    (
+        false,
+        // Rule 2: prefer matching scope.
+        scope(dest) != scope(source.ip()),
+        // Rule 5: prefer matching label.
+        policy(source.ip()).1 != label,
+        // Rule 6: prefer higher precedence.
+        Reverse(precedence),
+        // Rule 8: prefer smaller scope.
+        scope(dest),
+    )
+}
+
+/// The scope of an address, per RFC 6724 section 3.1 and 3.2.
+fn scope(ip: IpAddr) -> u8 {
+    match ip {
+        IpAddr::V4(ip) => {
+            let octets = ip.octets();
+            if octets[0] == 127 || (octets[0] == 169 && octets[1] == 254) {
+                SCOPE_LINK_LOCAL
+            } else {
+                SCOPE_GLOBAL
+            }
+        }
+        IpAddr::V6(ip) => {
+            let segments = ip.segments();
+            if segments[0] & 0xff00 == 0xff00 {
+                (segments[0] & 0x000f) as u8
+            } else if ip == Ipv6Addr::LOCALHOST || segments[0] & 0xffc0 == 0xfe80 {
+                SCOPE_LINK_LOCAL
+            } else if segments[0] & 0xffc0 == 0xfec0 {
+                SCOPE_SITE_LOCAL
+            } else {
+                SCOPE_GLOBAL
+            }
+        }
+    }
+}
+
+/// Look up (precedence, label) in the policy table, mapping IPv4 addresses into
+/// `::ffff:0:0/96`.
+fn policy(ip: IpAddr) -> (u8, u8) {
+    let ip = match ip {
+        IpAddr::V4(ip) => u128::from(ip.to_ipv6_mapped()),
+        IpAddr::V6(ip) => u128::from(ip),
+    };
+    for &(prefix, len, precedence, label) in POLICY_TABLE.iter() {
+        let mask = u128::MAX.checked_shl(128 - len).unwrap_or(0);
+        if ip & mask == prefix {
+            return (precedence, label);
+        }
+    }
+    unreachable!("::/0 matches every address")
+}
+
+/// Length of the prefix `source` has in common with `dest`, up to the source's own prefix
+/// length.
+fn common_prefix_len(source: &IfAddr, dest: IpAddr) -> u32 {
+    let (common, prefix_len) = match (source, dest) {
+        (IfAddr::V4(source), IpAddr::V4(dest)) => (
+            (u32::from(source.ip) ^ u32::from(dest)).leading_zeros(),
+            u32::from(source.netmask).leading_ones(),
+        ),
+        (IfAddr::V6(source), IpAddr::V6(dest)) => (
+            (u128::from(source.ip) ^ u128::from(dest)).leading_zeros(),
+            u128::from(source.netmask).leading_ones(),
+        ),
+        _ => return 0,
+    };
+    common.min(prefix_len)
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::{connection_candidates_with, sort_destination_addrs_with};
+    use crate::{IfAddr, Ifv4Addr, Ifv6Addr, Interface};
+    use std::net::IpAddr;
+
+    fn iface(addr: &str, prefix_len: u32) -> Interface {
+        let addr = match addr.parse().unwrap() {
+            IpAddr::V4(ip) => IfAddr::V4(Ifv4Addr {
+                ip,
+                netmask: (u32::MAX << (32 - prefix_len)).into(),
+                broadcast: None,
+            }),
+            IpAddr::V6(ip) => IfAddr::V6(Ifv6Addr {
+                ip,
+                netmask: (u128::MAX << (128 - prefix_len)).into(),
+                broadcast: None,
+            }),
+        };
+        Interface::new("test0".to_string(), addr, None)
+    }
+
+    fn sorted(dests: &[&str], ifaces: &[Interface]) -> Vec<IpAddr> {
+        let mut dests: Vec<IpAddr> = dests.iter().map(|d| d.parse().unwrap()).collect();
+        sort_destination_addrs_with(&mut dests, ifaces);
+        dests
+    }
+
+    fn parsed(dests: &[&str]) -> Vec<IpAddr> {
+        dests.iter().map(|d| d.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_rfc6724_examples() {
+        // The destination address selection examples of RFC 6724 section 10.2.
+        let ifaces = [
+            iface("2001:db8:1::2", 64),
+            iface("fe80::1", 64),
+            iface("169.254.13.78", 16),
+        ];
+        assert_eq!(
+            parsed(&["2001:db8:1::1", "198.51.100.121"]),
+            sorted(&["198.51.100.121", "2001:db8:1::1"], &ifaces)
+        );
+
+        let ifaces = [iface("fe80::1", 64), iface("198.51.100.117", 24)];
+        assert_eq!(
+            parsed(&["198.51.100.121", "2001:db8:1::1"]),
+            sorted(&["2001:db8:1::1", "198.51.100.121"], &ifaces)
+        );
+
+        let ifaces = [
+            iface("2001:db8:1::2", 64),
+            iface("fe80::1", 64),
+            iface("10.1.2.4", 8),
+        ];
+        assert_eq!(
+            parsed(&["2001:db8:1::1", "10.1.2.3"]),
+            sorted(&["10.1.2.3", "2001:db8:1::1"], &ifaces)
+        );
+
+        let ifaces = [iface("2001:db8:1::2", 64), iface("fe80::2", 64)];
+        assert_eq!(
+            parsed(&["fe80::1", "2001:db8:1::1"]),
+            sorted(&["2001:db8:1::1", "fe80::1"], &ifaces)
+        );
+
+        // Destinations without a source of their family go last.
+        let ifaces = [iface("192.168.1.10", 24)];
+        assert_eq!(
+            parsed(&["192.0.2.1", "2001:db8::1"]),
+            sorted(&["2001:db8::1", "192.0.2.1"], &ifaces)
+        );
+    }
+
+    #[test]
+    fn test_interleaved_families() {
+        // An IPv4-mapped IPv6 source gives IPv6 destinations the precedence and label of IPv4
+        // ones, so only rule 9, which never compares across families, tells these apart.
+        let ifaces = [iface("192.0.2.10", 24), iface("::ffff:198.51.100.10", 120)];
+        let (far, near, mapped) = ("203.0.113.1", "192.0.2.1", "::ffff:198.51.100.1");
+        assert_eq!(
+            parsed(&[near, mapped, far]),
+            sorted(&[far, mapped, near], &ifaces)
+        );
+        assert_eq!(
+            parsed(&[mapped, near, far]),
+            sorted(&[mapped, far, near], &ifaces)
+        );
+        assert_eq!(
+            parsed(&[near, "192.0.2.2", mapped, far, "203.0.113.2"]),
+            sorted(&[far, "203.0.113.2", mapped, near, "192.0.2.2"], &ifaces)
+        );
+    }
+
+    #[test]
+    fn test_down_interface() {
+        let mut down = iface("2001:db8:1::2", 64);
+        down.has_carrier = Some(false);
+        let ifaces = [down, iface("2001:db8:3::2", 64)];
+        let remotes = parsed(&["2001:db8:1::1"]);
+        let candidates = connection_candidates_with(&remotes, &ifaces);
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].0.ip(), ifaces[1].ip());
+
+        let ifaces = &ifaces[..1];
+        assert!(connection_candidates_with(&remotes, ifaces).is_empty());
+    }
+
+    #[test]
+    fn test_connection_candidates() {
+        let ifaces = [
+            iface("192.168.1.10", 24),
+            iface("2001:db8:1::2", 64),
+            iface("fe80::1", 64),
+        ];
+        let remotes = parsed(&[
+            "192.0.2.1",
+            "192.0.2.2",
+            "2001:db8:2::1",
+            "2001:db8:2::2",
+            "2001:db8:2::3",
+            "fe80::9",
+        ]);
+        let candidates: Vec<(IpAddr, IpAddr)> = connection_candidates_with(&remotes, &ifaces)
+            .into_iter()
+            .map(|(iface, remote)| (iface.ip(), remote))
+            .collect();
+        let expected: Vec<(IpAddr, IpAddr)> = [
+            ("fe80::1", "fe80::9"),
+            ("192.168.1.10", "192.0.2.1"),
+            ("2001:db8:1::2", "2001:db8:2::1"),
+            ("192.168.1.10", "192.0.2.2"),
+            ("2001:db8:1::2", "2001:db8:2::2"),
+            ("2001:db8:1::2", "2001:db8:2::3"),
+        ]
+        .iter()
+        .map(|(local, remote)| (local.parse().unwrap(), remote.parse().unwrap()))
+        .collect();
+        assert_eq!(expected, candidates);
+    }
+}
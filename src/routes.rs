@@ -0,0 +1,673 @@
+// Copyright 2018 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Access to the routing table of this host.
+
+use crate::{Cidr, Interface};
+use std::io;
+use std::net::{IpAddr, Ipv6Addr};
+use std::time::Duration;
+
+/// A route of this host.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Route {
+    /// The destination network address.
+    pub destination: IpAddr,
+    /// The prefix length of the destination network; `0` for a default route.
+    pub prefix_len: u8,
+    /// The next hop, if the destination is reached through a gateway rather than directly.
+    pub gateway: Option<IpAddr>,
+    /// The index of the outgoing interface.
+    pub index: u32,
+}
+
+impl Route {
+    /// Check whether this is a default route, i.e. it matches every destination of its family.
+    pub fn is_default(&self) -> bool {
+        self.prefix_len == 0
+    }
+
+    /// Get the destination network of this route.
+    pub fn cidr(&self) -> Cidr {
+        Cidr::saturating(self.destination, u32::from(self.prefix_len))
+    }
+}
+
+/// Get the routes of this host.
+///
+/// Which tables these come from depends on the platform. On Linux, the IPv4 routes are those
+/// of the main table, while the IPv6 routes come from every table, including the local one
+/// that holds the routes to this host's own addresses. macOS and Windows report all their
+/// routes, host routes included.
+pub fn get_routes() -> io::Result<Vec<Route>> {
+    os::get_routes()
+}
+
+/// An IPv6 default router learned through Neighbor Discovery from a Router Advertisement.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct DefaultRouter {
+    /// The link-local address of the router.
+    pub address: Ipv6Addr,
+    /// The index of the interface the advertisement was received on.
+    pub index: u32,
+    /// The remaining router lifetime, if known and finite.
+    pub lifetime: Option<Duration>,
+}
+
+/// Get the IPv6 default routers learned from Router Advertisements, e.g. to check that
+/// advertisements arrive on the expected interface.
+///
+/// The lifetime is reported on Windows, and on Linux with the `rustix` feature. macOS does not
+/// mark routes learned this way, so there every IPv6 default route through a link-local
+/// gateway is taken to be one.
+pub fn get_default_routers() -> io::Result<Vec<DefaultRouter>> {
+    os::get_default_routers()
+}
+
+/// Add `route` to the main routing table, like `ip route add`. A `route.index` of `0` leaves
+/// the outgoing interface to the operating system, which Windows does not support.
+///
+/// Needs administrative privileges.
+#[cfg(feature = "config")]
+pub fn add_route(route: &Route) -> io::Result<()> {
+    crate::config::os::add_route(route)
+}
+
+/// Delete `route` from the main routing table, like `ip route del`.
+///
+/// Needs administrative privileges.
+#[cfg(feature = "config")]
+pub fn delete_route(route: &Route) -> io::Result<()> {
+    crate::config::os::delete_route(route)
+}
+
+/// Check whether `iface` currently carries a default route for the family of its address.
+///
+/// The interface is matched on its index, falling back to its name if the index is unknown.
+pub fn has_default_route(iface: &Interface) -> io::Result<bool> {
+    let names = match iface.index {
+        Some(_) => Vec::new(),
+        None => crate::get_if_names()?,
+    };
+    Ok(has_default_route_in(&get_routes()?, iface, &names))
+}
+
+/// Check whether `routes` hold a default route through `iface` for the family of its address.
+/// An `iface` without an index is matched to its link in `names`, as listed by
+/// [`get_if_names`](crate::get_if_names).
+pub(crate) fn has_default_route_in(
+    routes: &[Route],
+    iface: &Interface,
+    names: &[(String, u32)],
+) -> bool {
+    let link = crate::link_name(&iface.name);
+    let index = match iface.index.or_else(|| {
+        names
+            .iter()
+            .find(|&(name, _)| name == link)
+            .map(|&(_, index)| index)
+    }) {
+        Some(index) => index,
+        None => return false,
+    };
+    let is_ipv4 = iface.ip().is_ipv4();
+    routes.iter().any(|route| {
+        route.is_default() && route.index == index && route.destination.is_ipv4() == is_ipv4
+    })
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod os {
+    use super::{DefaultRouter, Route};
+    use std::ffi::CString;
+    use std::fs;
+    use std::io;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+    #[cfg(all(feature = "rustix", target_os = "linux"))]
+    use std::time::Duration;
+
+    const RTF_UP: u32 = 0x1;
+    const RTF_GATEWAY: u32 = 0x2;
+    const RTF_REJECT: u32 = 0x200;
+
+    pub fn get_routes() -> io::Result<Vec<Route>> {
+        let mut ret = parse_route(&fs::read_to_string("/proc/net/route")?, if_nametoindex);
+        ret.extend(parse_ipv6_route(&read_ipv6_route()?, if_nametoindex));
+        Ok(ret)
+    }
+
+    #[cfg(all(feature = "rustix", target_os = "linux"))]
+    pub fn get_default_routers() -> io::Result<Vec<DefaultRouter>> {
+        Ok(crate::netlink::ra_default_routes()?
+            .into_iter()
+            .map(|route| DefaultRouter {
+                address: route.gateway,
+                index: route.index,
+                lifetime: route
+                    .expires
+                    .map(|expires| Duration::from_millis(u64::from(expires) * 10)),
+            })
+            .collect())
+    }
+
+    #[cfg(not(all(feature = "rustix", target_os = "linux")))]
+    pub fn get_default_routers() -> io::Result<Vec<DefaultRouter>> {
+        Ok(parse_ra_routers(&read_ipv6_route()?, if_nametoindex))
+    }
+
+    /// Read `/proc/net/ipv6_route`, which is missing when the kernel was booted with
+    /// `ipv6.disable=1`. That is read as no IPv6 routes.
+    fn read_ipv6_route() -> io::Result<String> {
+        match fs::read_to_string("/proc/net/ipv6_route") {
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+            ret => ret,
+        }
+    }
+
+    /// Parse `/proc/net/route`, resolving interface names with `index_of`.
+    pub(super) fn parse_route<F>(data: &str, index_of: F) -> Vec<Route>
+    where
+        F: Fn(&str) -> Option<u32>,
+    {
+        let mut ret = Vec::new();
+        // Iface Destination Gateway Flags RefCnt Use Metric Mask MTU Window IRTT, with
+        // addresses printed as native-endian hex words.
+        for line in data.lines().skip(1) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 8 {
+                continue;
+            }
+            let hex = |field: &str| u32::from_str_radix(field, 16).ok();
+            let (destination, gateway, flags, mask) = match (
+                hex(fields[1]),
+                hex(fields[2]),
+                hex(fields[3]),
+                hex(fields[7]),
+            ) {
+                (Some(d), Some(g), Some(f), Some(m)) => (d, g, f, m),
+                _ => continue,
+            };
+            if flags & RTF_UP == 0 || flags & RTF_REJECT != 0 {
+                continue;
+            }
+            let index = match index_of(fields[0]) {
+                Some(index) => index,
+                None => continue,
+            };
+            ret.push(Route {
+                destination: IpAddr::V4(Ipv4Addr::from(destination.to_ne_bytes())),
+                prefix_len: mask.count_ones() as u8,
+                gateway: if flags & RTF_GATEWAY != 0 {
+                    Some(IpAddr::V4(Ipv4Addr::from(gateway.to_ne_bytes())))
+                } else {
+                    None
+                },
+                index,
+            });
+        }
+        ret
+    }
+
+    /// Parse `/proc/net/ipv6_route`, resolving interface names with `index_of`.
+    pub(super) fn parse_ipv6_route<F>(data: &str, index_of: F) -> Vec<Route>
+    where
+        F: Fn(&str) -> Option<u32>,
+    {
+        let mut ret = Vec::new();
+        // Destination PrefixLen Source SourcePrefixLen NextHop Metric RefCnt Use Flags Iface,
+        // with addresses printed in network order.
+        for line in data.lines() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 10 {
+                continue;
+            }
+            let (destination, prefix_len, next_hop, flags) = match (
+                u128::from_str_radix(fields[0], 16),
+                u8::from_str_radix(fields[1], 16),
+                u128::from_str_radix(fields[4], 16),
+                u32::from_str_radix(fields[8], 16),
+            ) {
+                (Ok(d), Ok(p), Ok(n), Ok(f)) => (d, p, n, f),
+                _ => continue,
+            };
+            if flags & RTF_UP == 0 || flags & RTF_REJECT != 0 {
+                continue;
+            }
+            let index = match index_of(fields[9]) {
+                Some(index) => index,
+                None => continue,
+            };
+            ret.push(Route {
+                destination: IpAddr::V6(Ipv6Addr::from(destination)),
+                prefix_len,
+                gateway: if flags & RTF_GATEWAY != 0 {
+                    Some(IpAddr::V6(Ipv6Addr::from(next_hop)))
+                } else {
+                    None
+                },
+                index,
+            });
+        }
+        ret
+    }
+
+    /// Pick the default routes learned from Router Advertisements out of
+    /// `/proc/net/ipv6_route`. The remaining lifetime is not part of this file.
+    #[cfg(not(all(feature = "rustix", target_os = "linux")))]
+    pub(super) fn parse_ra_routers<F>(data: &str, index_of: F) -> Vec<DefaultRouter>
+    where
+        F: Fn(&str) -> Option<u32>,
+    {
+        const RTF_DEFAULT: u32 = 0x10000;
+        const RTF_ADDRCONF: u32 = 0x40000;
+
+        let mut ret = Vec::new();
+        for line in data.lines() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() < 10 {
+                continue;
+            }
+            let (next_hop, flags) = match (
+                u128::from_str_radix(fields[4], 16),
+                u32::from_str_radix(fields[8], 16),
+            ) {
+                (Ok(n), Ok(f)) => (n, f),
+                _ => continue,
+            };
+            let wanted = RTF_UP | RTF_GATEWAY | RTF_DEFAULT | RTF_ADDRCONF;
+            if flags & wanted != wanted {
+                continue;
+            }
+            if let Some(index) = index_of(fields[9]) {
+                ret.push(DefaultRouter {
+                    address: Ipv6Addr::from(next_hop),
+                    index,
+                    lifetime: None,
+                });
+            }
+        }
+        ret
+    }
+
+    #[allow(unsafe_code)]
+    fn if_nametoindex(name: &str) -> Option<u32> {
+        let name = CString::new(name).ok()?;
+        match unsafe { libc::if_nametoindex(name.as_ptr()) } {
+            0 => None,
+            index => Some(index),
+        }
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+mod os {
+    use super::{DefaultRouter, Route};
+    use std::io;
+    use std::mem;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+    use std::ptr;
+
+    const RTAX_MAX: usize = 8;
+    const RTAX_DST: usize = 0;
+    const RTAX_GATEWAY: usize = 1;
+    const RTAX_NETMASK: usize = 2;
+
+    #[allow(unsafe_code)]
+    pub fn get_routes() -> io::Result<Vec<Route>> {
+        let mut mib = [libc::CTL_NET, libc::PF_ROUTE, 0, 0, libc::NET_RT_DUMP, 0];
+        let mut len = 0;
+        let mut buf: Vec<u8> = Vec::new();
+        // The table may grow between sizing and reading it, so retry until it fits.
+        loop {
+            if unsafe {
+                libc::sysctl(
+                    mib.as_mut_ptr(),
+                    mib.len() as _,
+                    ptr::null_mut(),
+                    &mut len,
+                    ptr::null_mut(),
+                    0,
+                )
+            } == -1
+            {
+                return Err(io::Error::last_os_error());
+            }
+            buf.resize(len, 0);
+            if unsafe {
+                libc::sysctl(
+                    mib.as_mut_ptr(),
+                    mib.len() as _,
+                    buf.as_mut_ptr() as *mut _,
+                    &mut len,
+                    ptr::null_mut(),
+                    0,
+                )
+            } == -1
+            {
+                let err = io::Error::last_os_error();
+                if err.raw_os_error() == Some(libc::ENOMEM) {
+                    continue;
+                }
+                return Err(err);
+            }
+            buf.truncate(len);
+            break;
+        }
+
+        let mut ret = Vec::new();
+        let hdr_len = mem::size_of::<libc::rt_msghdr>();
+        let mut msgs = &buf[..];
+        while msgs.len() >= hdr_len {
+            let hdr: libc::rt_msghdr =
+                unsafe { ptr::read_unaligned(msgs.as_ptr() as *const libc::rt_msghdr) };
+            let msg_len = usize::from(hdr.rtm_msglen);
+            if msg_len < hdr_len || msg_len > msgs.len() {
+                break;
+            }
+            let msg = &msgs[..msg_len];
+            msgs = &msgs[msg_len..];
+
+            if hdr.rtm_flags & libc::RTF_UP == 0
+                || hdr.rtm_flags & (libc::RTF_REJECT | libc::RTF_BLACKHOLE) != 0
+            {
+                continue;
+            }
+
+            // The sockaddrs present in rtm_addrs follow the header, each padded to 4 bytes.
+            let mut addrs: [&[u8]; RTAX_MAX] = [&[]; RTAX_MAX];
+            let mut rest = &msg[hdr_len..];
+            for (i, addr) in addrs.iter_mut().enumerate() {
+                if hdr.rtm_addrs & (1 << i) == 0 || rest.is_empty() {
+                    continue;
+                }
+                let sa_len = usize::from(rest[0]).min(rest.len());
+                *addr = &rest[..sa_len];
+                let padded = if sa_len == 0 { 4 } else { (sa_len + 3) & !3 };
+                rest = &rest[padded.min(rest.len())..];
+            }
+
+            let destination = match to_ipaddr(addrs[RTAX_DST]) {
+                Some(destination) => destination,
+                None => continue,
+            };
+            let prefix_len = if hdr.rtm_flags & libc::RTF_HOST != 0 {
+                if destination.is_ipv4() {
+                    32
+                } else {
+                    128
+                }
+            } else {
+                netmask_len(addrs[RTAX_NETMASK], destination.is_ipv4())
+            };
+            let gateway = if hdr.rtm_flags & libc::RTF_GATEWAY != 0 {
+                to_ipaddr(addrs[RTAX_GATEWAY])
+            } else {
+                None
+            };
+            ret.push(Route {
+                destination,
+                prefix_len,
+                gateway,
+                index: u32::from(hdr.rtm_index),
+            });
+        }
+
+        Ok(ret)
+    }
+
+    pub fn get_default_routers() -> io::Result<Vec<DefaultRouter>> {
+        Ok(get_routes()?
+            .into_iter()
+            .filter(|route| route.is_default())
+            .filter_map(|route| match route.gateway {
+                Some(IpAddr::V6(address)) if crate::is_link_local(IpAddr::V6(address)) => {
+                    Some(DefaultRouter {
+                        address,
+                        index: route.index,
+                        lifetime: None,
+                    })
+                }
+                _ => None,
+            })
+            .collect())
+    }
+
+    fn to_ipaddr(sa: &[u8]) -> Option<IpAddr> {
+        if sa.len() < 2 {
+            return None;
+        }
+        match i32::from(sa[1]) {
+            libc::AF_INET if sa.len() >= 8 => {
+                let mut octets = [0; 4];
+                octets.copy_from_slice(&sa[4..8]);
+                Some(IpAddr::V4(Ipv4Addr::from(octets)))
+            }
+            libc::AF_INET6 if sa.len() >= 24 => {
+                let mut octets = [0; 16];
+                octets.copy_from_slice(&sa[8..24]);
+                // The kernel embeds the scope id of link-local addresses in the second word.
+                if octets[0] == 0xfe && octets[1] & 0xc0 == 0x80 {
+                    octets[2] = 0;
+                    octets[3] = 0;
+                }
+                Some(IpAddr::V6(Ipv6Addr::from(octets)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Prefix length of a netmask sockaddr, which the kernel truncates after its last non-zero
+    /// byte and may leave without a family.
+    fn netmask_len(sa: &[u8], is_ipv4: bool) -> u8 {
+        let offset = if is_ipv4 { 4 } else { 8 };
+        let max = if is_ipv4 { 4 } else { 16 };
+        let bytes = sa.get(offset..).unwrap_or(&[]);
+        let mut len = 0;
+        for &byte in bytes.iter().take(max) {
+            len += byte.leading_ones() as u8;
+            if byte != 0xff {
+                break;
+            }
+        }
+        len
+    }
+}
+
+#[cfg(windows)]
+mod os {
+    use super::{DefaultRouter, Route};
+    use std::io;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+    use std::time::Duration;
+    use std::{ptr, slice};
+    use winapi::shared::netioapi::{FreeMibTable, GetIpForwardTable2, PMIB_IPFORWARD_TABLE2};
+    use winapi::shared::nldef::NlroRouterAdvertisement;
+    use winapi::shared::winerror::NO_ERROR;
+    use winapi::shared::ws2def::{AF_INET, AF_INET6, AF_UNSPEC};
+    use winapi::shared::ws2ipdef::SOCKADDR_INET;
+
+    #[allow(unsafe_code)]
+    pub fn get_routes() -> io::Result<Vec<Route>> {
+        let mut table: PMIB_IPFORWARD_TABLE2 = ptr::null_mut();
+        let ret = unsafe { GetIpForwardTable2(AF_UNSPEC as u16, &mut table) };
+        if ret != NO_ERROR {
+            return Err(io::Error::from_raw_os_error(ret as i32));
+        }
+
+        let rows =
+            unsafe { slice::from_raw_parts((*table).Table.as_ptr(), (*table).NumEntries as usize) };
+        let routes = rows
+            .iter()
+            .filter_map(|row| {
+                let destination = to_ipaddr(&row.DestinationPrefix.Prefix)?;
+                let gateway = to_ipaddr(&row.NextHop).filter(|ip| !ip.is_unspecified());
+                Some(Route {
+                    destination,
+                    prefix_len: row.DestinationPrefix.PrefixLength,
+                    gateway,
+                    index: row.InterfaceIndex,
+                })
+            })
+            .collect();
+
+        unsafe { FreeMibTable(table as *mut _) };
+        Ok(routes)
+    }
+
+    #[allow(unsafe_code)]
+    pub fn get_default_routers() -> io::Result<Vec<DefaultRouter>> {
+        const INFINITE_LIFETIME: u32 = 0xffff_ffff;
+
+        let mut table: PMIB_IPFORWARD_TABLE2 = ptr::null_mut();
+        let ret = unsafe { GetIpForwardTable2(AF_INET6 as u16, &mut table) };
+        if ret != NO_ERROR {
+            return Err(io::Error::from_raw_os_error(ret as i32));
+        }
+
+        let rows =
+            unsafe { slice::from_raw_parts((*table).Table.as_ptr(), (*table).NumEntries as usize) };
+        let routers = rows
+            .iter()
+            .filter(|row| {
+                row.DestinationPrefix.PrefixLength == 0 && row.Origin == NlroRouterAdvertisement
+            })
+            .filter_map(|row| match to_ipaddr(&row.NextHop)? {
+                IpAddr::V6(address) => Some(DefaultRouter {
+                    address,
+                    index: row.InterfaceIndex,
+                    lifetime: match row.ValidLifetime {
+                        INFINITE_LIFETIME => None,
+                        secs => Some(Duration::from_secs(u64::from(secs))),
+                    },
+                }),
+                IpAddr::V4(_) => None,
+            })
+            .collect();
+
+        unsafe { FreeMibTable(table as *mut _) };
+        Ok(routers)
+    }
+
+    #[allow(unsafe_code)]
+    fn to_ipaddr(sa: &SOCKADDR_INET) -> Option<IpAddr> {
+        unsafe {
+            match i32::from(*sa.si_family()) {
+                AF_INET => {
+                    let s_addr = *sa.Ipv4().sin_addr.S_un.S_addr();
+                    Some(IpAddr::V4(Ipv4Addr::from(u32::from_be(s_addr))))
+                }
+                AF_INET6 => Some(IpAddr::V6(Ipv6Addr::from(*sa.Ipv6().sin6_addr.u.Byte()))),
+                _ => None,
+            }
+        }
+    }
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    windows
+)))]
+mod os {
+    use super::{DefaultRouter, Route};
+    use std::io;
+
+    pub fn get_routes() -> io::Result<Vec<Route>> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "reading the routing table is not supported on this platform",
+        ))
+    }
+
+    pub fn get_default_routers() -> io::Result<Vec<DefaultRouter>> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "reading the routing table is not supported on this platform",
+        ))
+    }
+}
+
+#[cfg(all(test, any(target_os = "linux", target_os = "android")))]
+mod tests {
+    use super::os::{parse_ipv6_route, parse_route};
+    use std::net::IpAddr;
+
+    fn index_of(name: &str) -> Option<u32> {
+        match name {
+            "lo" => Some(1),
+            "eth0" => Some(2),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_parse_route() {
+        let data = "\
+Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT
+eth0\t00000000\t010200C0\t0003\t0\t0\t100\t00000000\t0\t0\t0
+eth0\t000200C0\t00000000\t0001\t0\t0\t100\t00FFFFFF\t0\t0\t0
+gone0\t000A000A\t00000000\t0001\t0\t0\t0\t00FFFFFF\t0\t0\t0
+";
+        let routes = parse_route(data, index_of);
+        assert_eq!(routes.len(), 2);
+        assert!(routes[0].is_default());
+        assert_eq!(routes[0].index, 2);
+        if cfg!(target_endian = "little") {
+            assert_eq!(routes[0].gateway, Some("192.0.2.1".parse().unwrap()));
+            assert_eq!(
+                routes[1].destination,
+                "192.0.2.0".parse::<IpAddr>().unwrap()
+            );
+        }
+        assert_eq!(routes[1].prefix_len, 24);
+        assert_eq!(routes[1].gateway, None);
+    }
+
+    #[test]
+    fn test_parse_ipv6_route() {
+        let data = "\
+20010db8000000000000000000000000 40 00000000000000000000000000000000 00 00000000000000000000000000000000 00000100 00000001 00000000 00000001     eth0
+00000000000000000000000000000000 00 00000000000000000000000000000000 00 fe800000000000000000000000000001 00000400 00000001 00000000 00450003     eth0
+00000000000000000000000000000000 00 00000000000000000000000000000000 00 00000000000000000000000000000000 ffffffff 00000001 00000000 00200200       lo
+00000000000000000000000000000001 80 00000000000000000000000000000000 00 00000000000000000000000000000000 00000000 00000002 00000000 80200001       lo
+";
+        let routes = parse_ipv6_route(data, index_of);
+        assert_eq!(routes.len(), 3);
+        assert_eq!(
+            routes[0].destination,
+            "2001:db8::".parse::<IpAddr>().unwrap()
+        );
+        assert_eq!(routes[0].prefix_len, 64);
+        assert_eq!(routes[0].gateway, None);
+        assert!(routes[1].is_default());
+        assert_eq!(routes[1].gateway, Some("fe80::1".parse().unwrap()));
+        assert_eq!(routes[2].index, 1);
+
+        // The file is missing when IPv6 is disabled, which reads as empty.
+        assert!(parse_ipv6_route("", index_of).is_empty());
+    }
+
+    #[cfg(not(all(feature = "rustix", target_os = "linux")))]
+    #[test]
+    fn test_parse_ra_routers() {
+        let data = "\
+00000000000000000000000000000000 00 00000000000000000000000000000000 00 fe800000000000000000000000000001 00000400 00000001 00000000 00450003     eth0
+00000000000000000000000000000000 00 00000000000000000000000000000000 00 fe800000000000000000000000000002 00000400 00000001 00000000 00000003     eth0
+";
+        let routers = super::os::parse_ra_routers(data, index_of);
+        assert_eq!(routers.len(), 1);
+        assert_eq!(
+            routers[0].address,
+            "fe80::1".parse::<std::net::Ipv6Addr>().unwrap()
+        );
+        assert_eq!(routers[0].index, 2);
+    }
+}
@@ -8,111 +8,275 @@
 // Software.
 
 #[cfg(not(windows))]
-use libc::{sockaddr, sockaddr_in, sockaddr_in6, AF_INET, AF_INET6};
-use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
-use std::ptr::NonNull;
+use libc::{sockaddr, sockaddr_in, sockaddr_in6, AF_INET, AF_INET6, AF_UNSPEC};
+use socket2::{socklen_t, SockAddr, SockAddrStorage};
+use std::mem;
+use std::net::IpAddr;
+use std::ptr;
 #[cfg(windows)]
 use winapi::{
-    shared::ws2def::{AF_INET, AF_INET6, SOCKADDR as sockaddr, SOCKADDR_IN as sockaddr_in},
+    shared::ws2def::{
+        AF_INET, AF_INET6, AF_UNSPEC, SOCKADDR as sockaddr, SOCKADDR_IN as sockaddr_in,
+    },
     shared::ws2ipdef::SOCKADDR_IN6 as sockaddr_in6,
 };
 
 pub fn to_ipaddr(sockaddr: *const sockaddr) -> Option<IpAddr> {
+    match to_sockaddr(sockaddr, None)?.as_socket()?.ip() {
+        // Ignore all fe80:: addresses as these are link locals
+        ip if ip.is_ipv6() && crate::is_link_local(ip) => None,
+        ip => Some(ip),
+    }
+}
+
+/// Read the netmask `sockaddr` points to for the address `addr`.
+///
+/// Some BSD drivers hand out netmasks that are cut short after their last non-zero byte, or
+/// leave the family unset; these are read in the family of `addr`.
+#[cfg(not(windows))]
+pub fn to_netmask(sockaddr: *const sockaddr, addr: IpAddr) -> Option<IpAddr> {
+    let family = match addr {
+        IpAddr::V4(_) => AF_INET,
+        IpAddr::V6(_) => AF_INET6,
+    };
+    Some(to_sockaddr(sockaddr, Some(family))?.as_socket()?.ip())
+}
+
+/// Check whether the IPv4 or IPv6 sockaddr `sockaddr` points to is cut short before the end of
+/// its address. Only sockaddrs that carry `sa_len` can be seen to be cut short.
+#[cfg(not(windows))]
+#[allow(unsafe_code)]
+pub fn is_truncated(sockaddr: *const sockaddr) -> bool {
     if sockaddr.is_null() {
-        return None;
+        return false;
     }
-    SockAddr::new(sockaddr)?.as_ipaddr()
+    let available = available_len(sockaddr);
+    if available < 2 {
+        return false;
+    }
+    // Where `sin_addr` and `sin6_addr` end.
+    let needed = match i32::from(unsafe { (*sockaddr).sa_family }) {
+        AF_INET => 8,
+        AF_INET6 => 24,
+        _ => return false,
+    };
+    available < needed
 }
 
-// Wrapper around a sockaddr pointer. Guaranteed to not be null.
-struct SockAddr {
-    inner: NonNull<sockaddr>,
+/// The number of bytes that may be read from the non-null `sockaddr`, `usize::MAX` where
+/// sockaddrs carry no `sa_len`.
+#[allow(unsafe_code)]
+fn available_len(sockaddr: *const sockaddr) -> usize {
+    #[cfg(any(
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "macos",
+        target_os = "openbsd",
+        target_os = "netbsd"
+    ))]
+    {
+        usize::from(unsafe { (*sockaddr).sa_len })
+    }
+    #[cfg(not(any(
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "macos",
+        target_os = "openbsd",
+        target_os = "netbsd"
+    )))]
+    {
+        let _ = sockaddr;
+        usize::MAX
+    }
 }
 
-impl SockAddr {
-    #[allow(clippy::new_ret_no_self)]
-    fn new(sockaddr: *const sockaddr) -> Option<Self> {
-        NonNull::new(sockaddr as *mut _).map(|inner| Self { inner })
-    }
-
-    #[cfg(not(windows))]
-    fn as_ipaddr(&self) -> Option<IpAddr> {
-        match self.sockaddr_in() {
-            Some(SockAddrIn::In(sa)) => Some(IpAddr::V4(Ipv4Addr::new(
-                ((sa.sin_addr.s_addr) & 255) as u8,
-                ((sa.sin_addr.s_addr >> 8) & 255) as u8,
-                ((sa.sin_addr.s_addr >> 16) & 255) as u8,
-                ((sa.sin_addr.s_addr >> 24) & 255) as u8,
-            ))),
-            Some(SockAddrIn::In6(sa)) => {
-                // Ignore all fe80:: addresses as these are link locals
-                if sa.sin6_addr.s6_addr[0] == 0xfe && sa.sin6_addr.s6_addr[1] == 0x80 {
-                    return None;
-                }
-                Some(IpAddr::V6(Ipv6Addr::from(sa.sin6_addr.s6_addr)))
-            }
-            None => None,
-        }
+/// Copy the IPv4 or IPv6 sockaddr `sockaddr` points to, which has no length attached on most
+/// platforms, into a `SockAddr` sized by its family.
+///
+/// Where sockaddrs carry `sa_len`, no more than that many bytes are read and the rest is left
+/// zeroed. A sockaddr without a family is read as `family`, if given.
+#[allow(unsafe_code)]
+fn to_sockaddr(sockaddr: *const sockaddr, family: Option<i32>) -> Option<SockAddr> {
+    if sockaddr.is_null() {
+        return None;
     }
+    let available = available_len(sockaddr);
 
-    #[cfg(windows)]
-    fn as_ipaddr(&self) -> Option<IpAddr> {
-        match self.sockaddr_in() {
-            Some(SockAddrIn::In(sa)) => {
-                let s_addr = unsafe { sa.sin_addr.S_un.S_addr() };
-                // Ignore all 169.254.x.x addresses as these are not active interfaces
-                if s_addr & 65535 == 0xfea9 {
-                    return None;
-                }
-                Some(IpAddr::V4(Ipv4Addr::new(
-                    ((s_addr >> 0) & 255u32) as u8,
-                    ((s_addr >> 8) & 255u32) as u8,
-                    ((s_addr >> 16) & 255u32) as u8,
-                    ((s_addr >> 24) & 255u32) as u8,
-                )))
-            }
-            Some(SockAddrIn::In6(sa)) => {
-                let s6_addr = unsafe { sa.sin6_addr.u.Byte() };
-                // Ignore all fe80:: addresses as these are link locals
-                if s6_addr[0] == 0xfe && s6_addr[1] == 0x80 {
-                    return None;
-                }
-                Some(IpAddr::V6(Ipv6Addr::from(s6_addr.clone())))
-            }
-            None => None,
+    // `sa_family` ends at the same offset on every platform, after `sa_len` where there is one.
+    let own_family = if available >= 2 {
+        i32::from(unsafe { (*sockaddr).sa_family })
+    } else {
+        AF_UNSPEC
+    };
+    let family = match (own_family, family) {
+        (AF_UNSPEC, Some(family)) => family,
+        (own_family, _) => own_family,
+    };
+    let len = match family {
+        AF_INET => mem::size_of::<sockaddr_in>(),
+        AF_INET6 => mem::size_of::<sockaddr_in6>(),
+        _ => return None,
+    };
+    let mut storage = SockAddrStorage::zeroed();
+    unsafe {
+        let dst = &mut storage as *mut SockAddrStorage as *mut u8;
+        ptr::copy_nonoverlapping(sockaddr as *const u8, dst, len.min(available));
+        let dst = dst as *mut sockaddr;
+        (*dst).sa_family = family as _;
+        #[cfg(any(
+            target_os = "freebsd",
+            target_os = "ios",
+            target_os = "macos",
+            target_os = "openbsd",
+            target_os = "netbsd"
+        ))]
+        {
+            (*dst).sa_len = len as u8;
         }
+        Some(SockAddr::new(storage, len as socklen_t))
     }
+}
 
-    fn sockaddr_in(&self) -> Option<SockAddrIn> {
-        const AF_INET_U32: u32 = AF_INET as u32;
-        const AF_INET6_U32: u32 = AF_INET6 as u32;
+/// Read a 6-byte hardware address and the interface index out of an `AF_PACKET` link-layer
+/// sockaddr.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[allow(unsafe_code)]
+#[allow(clippy::cast_ptr_alignment)]
+pub fn to_hwaddr(sockaddr: *const sockaddr) -> Option<([u8; 6], u32)> {
+    if sockaddr.is_null() || i32::from(unsafe { (*sockaddr).sa_family }) != libc::AF_PACKET {
+        return None;
+    }
+    let sll = unsafe { *(sockaddr as *const libc::sockaddr_ll) };
+    if sll.sll_halen != 6 {
+        return None;
+    }
+    let mut hw_addr = [0; 6];
+    hw_addr.copy_from_slice(&sll.sll_addr[..6]);
+    Some((hw_addr, sll.sll_ifindex as u32))
+}
 
-        match self.sa_family() {
-            AF_INET_U32 => Some(SockAddrIn::In(self.sa_in())),
-            AF_INET6_U32 => Some(SockAddrIn::In6(self.sa_in6())),
-            _ => None,
+/// Read a 6-byte hardware address and the interface index out of an `AF_LINK` link-layer
+/// sockaddr.
+#[cfg(any(
+    target_os = "freebsd",
+    target_os = "ios",
+    target_os = "macos",
+    target_os = "openbsd",
+    target_os = "netbsd"
+))]
+#[allow(unsafe_code)]
+#[allow(clippy::cast_ptr_alignment)]
+pub fn to_hwaddr(sockaddr: *const sockaddr) -> Option<([u8; 6], u32)> {
+    if sockaddr.is_null() || i32::from(unsafe { (*sockaddr).sa_family }) != libc::AF_LINK {
+        return None;
+    }
+    let sdl = sockaddr as *const libc::sockaddr_dl;
+    unsafe {
+        if (*sdl).sdl_alen != 6 {
+            return None;
         }
+        // The address follows the name in sdl_data, and may extend past the declared array.
+        let data = std::ptr::addr_of!((*sdl).sdl_data) as *const u8;
+        let mut hw_addr = [0; 6];
+        std::ptr::copy_nonoverlapping(
+            data.add(usize::from((*sdl).sdl_nlen)),
+            hw_addr.as_mut_ptr(),
+            6,
+        );
+        Some((hw_addr, u32::from((*sdl).sdl_index)))
     }
+}
+
+#[cfg(all(test, not(windows)))]
+mod tests {
+    use super::{is_truncated, to_ipaddr, to_netmask};
+    use libc::{sockaddr, sockaddr_in, sockaddr_in6, AF_INET, AF_INET6, AF_UNSPEC};
+    use std::mem;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 
     #[allow(unsafe_code)]
-    fn sa_family(&self) -> u32 {
-        unsafe { u32::from(self.inner.as_ref().sa_family) }
+    fn sockaddr_in(family: i32, ip: Ipv4Addr) -> sockaddr_in {
+        let mut sin: sockaddr_in = unsafe { mem::zeroed() };
+        sin.sin_family = family as _;
+        sin.sin_addr.s_addr = u32::from(ip).to_be();
+        sin
     }
 
     #[allow(unsafe_code)]
-    #[allow(clippy::cast_ptr_alignment)]
-    fn sa_in(&self) -> sockaddr_in {
-        unsafe { *(self.inner.as_ptr() as *const sockaddr_in) }
+    fn sockaddr_in6(family: i32, ip: Ipv6Addr) -> sockaddr_in6 {
+        let mut sin6: sockaddr_in6 = unsafe { mem::zeroed() };
+        sin6.sin6_family = family as _;
+        sin6.sin6_addr.s6_addr = ip.octets();
+        sin6
     }
 
-    #[allow(unsafe_code)]
-    #[allow(clippy::cast_ptr_alignment)]
-    fn sa_in6(&self) -> sockaddr_in6 {
-        unsafe { *(self.inner.as_ptr() as *const sockaddr_in6) }
+    #[test]
+    fn test_to_ipaddr() {
+        let ip = Ipv4Addr::new(192, 0, 2, 1);
+        let sin = sockaddr_in(AF_INET, ip);
+        assert_eq!(
+            to_ipaddr(&sin as *const sockaddr_in as *const sockaddr),
+            Some(IpAddr::V4(ip))
+        );
+        let sin6 = sockaddr_in6(AF_INET6, "fe80::1".parse().unwrap());
+        assert_eq!(
+            to_ipaddr(&sin6 as *const sockaddr_in6 as *const sockaddr),
+            None
+        );
+        assert_eq!(to_ipaddr(std::ptr::null()), None);
     }
-}
 
-enum SockAddrIn {
-    In(sockaddr_in),
-    In6(sockaddr_in6),
+    #[test]
+    fn test_to_netmask_without_family() {
+        let addr = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+        let mask = Ipv4Addr::new(255, 255, 255, 0);
+        let sin = sockaddr_in(AF_UNSPEC, mask);
+        let sin = &sin as *const sockaddr_in as *const sockaddr;
+        assert_eq!(to_netmask(sin, addr), Some(IpAddr::V4(mask)));
+        assert!(!is_truncated(sin));
+
+        let addr = IpAddr::V6("2001:db8::1".parse().unwrap());
+        let mask = Ipv6Addr::from(!0u128 << 64);
+        let sin6 = sockaddr_in6(AF_UNSPEC, mask);
+        let sin6 = &sin6 as *const sockaddr_in6 as *const sockaddr;
+        assert_eq!(to_netmask(sin6, addr), Some(IpAddr::V6(mask)));
+        assert_eq!(to_netmask(std::ptr::null(), addr), None);
+    }
+
+    #[cfg(any(
+        target_os = "freebsd",
+        target_os = "ios",
+        target_os = "macos",
+        target_os = "openbsd",
+        target_os = "netbsd"
+    ))]
+    #[test]
+    fn test_short_sa_len() {
+        let addr = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+        // A netmask cut short after its last non-zero byte, with no family.
+        let mut sin = sockaddr_in(AF_UNSPEC, Ipv4Addr::new(255, 255, 255, 255));
+        sin.sin_len = 7;
+        let ptr = &sin as *const sockaddr_in as *const sockaddr;
+        assert_eq!(
+            to_netmask(ptr, addr),
+            Some(IpAddr::V4(Ipv4Addr::new(255, 255, 255, 0)))
+        );
+        // Without a family, there is no telling where the address ends.
+        assert!(!is_truncated(ptr));
+
+        sin.sin_family = AF_INET as _;
+        assert!(is_truncated(ptr));
+        sin.sin_len = mem::size_of::<sockaddr_in>() as u8;
+        assert!(!is_truncated(ptr));
+
+        let mut sin6 = sockaddr_in6(AF_INET6, "2001:db8::1".parse().unwrap());
+        sin6.sin6_len = 16;
+        let ptr = &sin6 as *const sockaddr_in6 as *const sockaddr;
+        assert!(is_truncated(ptr));
+        // The bytes past `sa_len` are not read.
+        assert_eq!(
+            to_ipaddr(ptr),
+            Some(IpAddr::V6("2001:db8::".parse().unwrap()))
+        );
+    }
 }
@@ -0,0 +1,144 @@
+// Copyright 2018 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::{IfAddr, Ifv4Addr, Ifv6Addr, Interface};
+#[cfg(not(feature = "std"))]
+use core::net::{SocketAddrV4, SocketAddrV6};
+#[cfg(all(
+    feature = "std",
+    any(target_os = "linux", target_os = "android", windows)
+))]
+use socket2::InterfaceIndexOrAddress;
+use socket2::SockAddr;
+#[cfg(feature = "std")]
+use socket2::SockRef;
+#[cfg(feature = "std")]
+use std::io;
+#[cfg(feature = "std")]
+use std::net::IpAddr;
+#[cfg(feature = "std")]
+use std::net::{SocketAddrV4, SocketAddrV6};
+
+impl Interface {
+    /// The address of this interface with `port`, e.g. to bind a socket to it. IPv6 addresses
+    /// are scoped to the interface index, so link-local addresses are usable as well.
+    pub fn to_sockaddr(&self, port: u16) -> SockAddr {
+        match self.addr {
+            IfAddr::V4(ref addr) => addr.to_sockaddr(port),
+            IfAddr::V6(ref addr) => {
+                SocketAddrV6::new(addr.ip, port, 0, self.index.unwrap_or(0)).into()
+            }
+        }
+    }
+}
+
+impl IfAddr {
+    /// This address with `port`, without an IPv6 scope.
+    pub fn to_sockaddr(&self, port: u16) -> SockAddr {
+        match *self {
+            IfAddr::V4(ref addr) => addr.to_sockaddr(port),
+            IfAddr::V6(ref addr) => addr.to_sockaddr(port),
+        }
+    }
+}
+
+impl Ifv4Addr {
+    /// This address with `port`.
+    pub fn to_sockaddr(&self, port: u16) -> SockAddr {
+        SocketAddrV4::new(self.ip, port).into()
+    }
+}
+
+impl Ifv6Addr {
+    /// This address with `port`, without a scope. Use [`Interface::to_sockaddr`] for
+    /// link-local addresses.
+    pub fn to_sockaddr(&self, port: u16) -> SockAddr {
+        SocketAddrV6::new(self.ip, port, 0, 0).into()
+    }
+}
+
+/// Multicast group membership on a chosen interface, for `std::net::UdpSocket` and
+/// `socket2::Socket`.
+///
+/// IPv6 groups are joined on the interface index. IPv4 groups are joined on the index on Linux,
+/// Android and Windows, which also works for interfaces without an IPv4 address, and on the
+/// interface's IPv4 address elsewhere.
+#[cfg(feature = "std")]
+pub trait MulticastExt {
+    /// Join the multicast group `group` on `iface`, e.g. `224.0.0.251` or `ff02::fb` for mDNS.
+    fn join_multicast_on(&self, group: IpAddr, iface: &Interface) -> io::Result<()>;
+
+    /// Leave the multicast group `group` on `iface`.
+    fn leave_multicast_on(&self, group: IpAddr, iface: &Interface) -> io::Result<()>;
+}
+
+#[cfg(feature = "std")]
+impl MulticastExt for std::net::UdpSocket {
+    fn join_multicast_on(&self, group: IpAddr, iface: &Interface) -> io::Result<()> {
+        membership(SockRef::from(self), group, iface, true)
+    }
+
+    fn leave_multicast_on(&self, group: IpAddr, iface: &Interface) -> io::Result<()> {
+        membership(SockRef::from(self), group, iface, false)
+    }
+}
+
+#[cfg(feature = "std")]
+impl MulticastExt for socket2::Socket {
+    fn join_multicast_on(&self, group: IpAddr, iface: &Interface) -> io::Result<()> {
+        membership(SockRef::from(self), group, iface, true)
+    }
+
+    fn leave_multicast_on(&self, group: IpAddr, iface: &Interface) -> io::Result<()> {
+        membership(SockRef::from(self), group, iface, false)
+    }
+}
+
+#[cfg(feature = "std")]
+fn membership(socket: SockRef<'_>, group: IpAddr, iface: &Interface, join: bool) -> io::Result<()> {
+    match group {
+        IpAddr::V4(group) => {
+            #[cfg(any(target_os = "linux", target_os = "android", windows))]
+            {
+                if let Some(index) = iface.index {
+                    let index = InterfaceIndexOrAddress::Index(index);
+                    return if join {
+                        socket.join_multicast_v4_n(&group, &index)
+                    } else {
+                        socket.leave_multicast_v4_n(&group, &index)
+                    };
+                }
+            }
+            let addr = match iface.addr {
+                IfAddr::V4(ref addr) => addr.ip,
+                IfAddr::V6(_) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "interface has neither an index nor an IPv4 address",
+                    ))
+                }
+            };
+            if join {
+                socket.join_multicast_v4(&group, &addr)
+            } else {
+                socket.leave_multicast_v4(&group, &addr)
+            }
+        }
+        IpAddr::V6(group) => {
+            let index = iface.index.ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidInput, "interface index is unknown")
+            })?;
+            if join {
+                socket.join_multicast_v6(&group, index)
+            } else {
+                socket.leave_multicast_v6(&group, index)
+            }
+        }
+    }
+}
@@ -0,0 +1,93 @@
+// Copyright 2018 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::options::Lookups;
+use crate::Interface;
+use std::io;
+use std::sync::{PoisonError, RwLock};
+
+static SOURCE: RwLock<Option<Box<dyn IfAddrsSource>>> = RwLock::new(None);
+
+/// A provider of interface details.
+///
+/// The operating system backends are exposed as [`OsSource`]. Applications can implement this
+/// trait to feed interfaces from elsewhere, e.g. a management daemon or a remote agent, and
+/// register it with [`set_source`].
+pub trait IfAddrsSource: Send + Sync {
+    /// Return the interfaces currently known to this source.
+    fn get_if_addrs(&self) -> io::Result<Vec<Interface>>;
+}
+
+/// The interfaces of this host, as reported by the operating system.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct OsSource;
+
+impl IfAddrsSource for OsSource {
+    fn get_if_addrs(&self) -> io::Result<Vec<Interface>> {
+        crate::os_get_if_addrs()
+    }
+}
+
+/// Register `source` to be queried by [`get_if_addrs`](crate::get_if_addrs) in place of the
+/// operating system.
+pub fn set_source<S: IfAddrsSource + 'static>(source: S) {
+    *SOURCE.write().unwrap_or_else(PoisonError::into_inner) = Some(Box::new(source));
+}
+
+/// Remove any source registered with [`set_source`], going back to the operating system.
+pub fn clear_source() {
+    *SOURCE.write().unwrap_or_else(PoisonError::into_inner) = None;
+}
+
+pub(crate) fn get_if_addrs() -> io::Result<Vec<Interface>> {
+    get_if_addrs_with(Lookups::DEFAULT)
+}
+
+/// Like `get_if_addrs`, making only the given `lookups`.
+pub(crate) fn get_if_addrs_with(lookups: Lookups) -> io::Result<Vec<Interface>> {
+    let mut ifaces = Vec::new();
+    get_if_addrs_into(lookups, &mut ifaces)?;
+    Ok(ifaces)
+}
+
+/// Like `get_if_addrs_with`, pushing the interfaces onto `ifaces`.
+pub(crate) fn get_if_addrs_into(lookups: Lookups, ifaces: &mut Vec<Interface>) -> io::Result<()> {
+    let start = ifaces.len();
+    merged(lookups, ifaces)?;
+    if lookups != Lookups::DEFAULT {
+        for iface in &mut ifaces[start..] {
+            lookups.apply(iface);
+        }
+    }
+    Ok(())
+}
+
+fn merged(lookups: Lookups, ifaces: &mut Vec<Interface>) -> io::Result<()> {
+    #[cfg(feature = "env-override")]
+    {
+        if let Some(over) = crate::env_override::from_env()? {
+            if over.augment {
+                query(lookups, ifaces)?;
+            }
+            ifaces.extend(over.interfaces);
+            return Ok(());
+        }
+    }
+    query(lookups, ifaces)
+}
+
+fn query(lookups: Lookups, ifaces: &mut Vec<Interface>) -> io::Result<()> {
+    match *SOURCE.read().unwrap_or_else(PoisonError::into_inner) {
+        Some(ref source) => {
+            ifaces.extend(source.get_if_addrs()?);
+            Ok(())
+        }
+        None => crate::os_get_if_addrs_into(lookups, ifaces, &mut Vec::new()),
+    }
+}
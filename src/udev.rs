@@ -0,0 +1,125 @@
+// Copyright 2018 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! udev properties of network devices on Linux, such as the predictable names udev derives
+//! from the device path and the hardware model.
+//!
+//! These are read from the udev database under `/run/udev/data`, so no udev library is needed,
+//! but the properties are only there on hosts running udev or systemd-udevd.
+
+use crate::Interface;
+use std::collections::BTreeMap;
+use std::ffi::CString;
+use std::fs;
+use std::io;
+
+const UDEV_DATA_DIR: &str = "/run/udev/data";
+
+/// The udev properties of a network device.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Default)]
+pub struct UdevInfo {
+    /// All properties udev recorded for the device, such as `ID_NET_DRIVER` or `ID_VENDOR_ID`.
+    pub properties: BTreeMap<String, String>,
+}
+
+impl UdevInfo {
+    /// Get the property `key`.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.properties.get(key).map(String::as_str)
+    }
+
+    /// The predictable name derived from the physical location of the device, e.g. `enp0s31f6`
+    /// (`ID_NET_NAME_PATH`).
+    pub fn net_name_path(&self) -> Option<&str> {
+        self.get("ID_NET_NAME_PATH")
+    }
+
+    /// The model of the device, from the hardware database if the device does not report one
+    /// itself (`ID_MODEL` or `ID_MODEL_FROM_DATABASE`).
+    pub fn model(&self) -> Option<&str> {
+        self.get("ID_MODEL")
+            .or_else(|| self.get("ID_MODEL_FROM_DATABASE"))
+    }
+
+    /// The bus the device sits on, e.g. `pci` or `usb` (`ID_BUS`).
+    pub fn bus(&self) -> Option<&str> {
+        self.get("ID_BUS")
+    }
+}
+
+/// Get the udev properties of `iface`.
+///
+/// Returns `None` if udev has no record of the interface, e.g. for virtual devices created
+/// after boot on some distributions, or if udev is not running.
+pub fn udev_info(iface: &Interface) -> io::Result<Option<UdevInfo>> {
+    let index = match iface.index.or_else(|| if_nametoindex(&iface.name)) {
+        Some(index) => index,
+        None => return Ok(None),
+    };
+    match fs::read_to_string(format!("{}/n{}", UDEV_DATA_DIR, index)) {
+        Ok(data) => Ok(Some(parse(&data))),
+        Err(ref err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Get the interfaces like [`get_if_addrs`](crate::get_if_addrs), each joined with its udev
+/// properties.
+pub fn get_if_addrs_with_udev() -> io::Result<Vec<(Interface, Option<UdevInfo>)>> {
+    crate::get_if_addrs()?
+        .into_iter()
+        .map(|iface| {
+            let info = udev_info(&iface)?;
+            Ok((iface, info))
+        })
+        .collect()
+}
+
+/// Parse a udev database record, whose properties are the `E:KEY=VALUE` lines.
+fn parse(data: &str) -> UdevInfo {
+    let properties = data
+        .lines()
+        .filter_map(|line| line.strip_prefix("E:"))
+        .filter_map(|property| {
+            let mut parts = property.splitn(2, '=');
+            Some((parts.next()?.to_string(), parts.next()?.to_string()))
+        })
+        .collect();
+    UdevInfo { properties }
+}
+
+#[allow(unsafe_code)]
+fn if_nametoindex(name: &str) -> Option<u32> {
+    let name = CString::new(name).ok()?;
+    match unsafe { libc::if_nametoindex(name.as_ptr()) } {
+        0 => None,
+        index => Some(index),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+
+    #[test]
+    fn test_parse() {
+        let info = parse(
+            "I:4259722\n\
+             E:ID_NET_NAMING_SCHEME=v252\n\
+             E:ID_NET_NAME_PATH=enp0s31f6\n\
+             E:ID_BUS=pci\n\
+             E:ID_MODEL_FROM_DATABASE=Ethernet Connection (4) I219-LM\n\
+             G:systemd\n",
+        );
+        assert_eq!(info.net_name_path(), Some("enp0s31f6"));
+        assert_eq!(info.bus(), Some("pci"));
+        assert_eq!(info.model(), Some("Ethernet Connection (4) I219-LM"));
+        assert_eq!(info.properties.len(), 4);
+    }
+}
@@ -0,0 +1,280 @@
+// Copyright 2018 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Blocking waits for interface state. There is no change notification to build on, so these
+//! re-enumerate at a fixed interval.
+
+use crate::Interface;
+use std::io;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Wait until an interface named `name` exists and is up (see [`Interface::is_up`]), and
+/// return its first address.
+///
+/// Returns `None` if `timeout` elapses first. Without a timeout this waits indefinitely.
+pub fn wait_for_interface(name: &str, timeout: Option<Duration>) -> io::Result<Option<Interface>> {
+    poll(timeout, |iface| iface.name == name && iface.is_up())
+}
+
+/// Wait until some interface has an address satisfying `predicate`, e.g. a global IPv6 or a
+/// non-link-local IPv4 address, and return it.
+///
+/// Returns `None` if `timeout` elapses first. Without a timeout this waits indefinitely.
+pub fn wait_for_address<F>(predicate: F, timeout: Option<Duration>) -> io::Result<Option<Interface>>
+where
+    F: FnMut(&Interface) -> bool,
+{
+    poll(timeout, predicate)
+}
+
+/// What [`wait_for_network_online`] waits for, beyond an interface that is up with a usable
+/// address.
+#[derive(Debug, Default, PartialEq, Eq, Hash, Clone)]
+pub struct OnlineRequirements {
+    default_route: bool,
+    interface: Option<String>,
+}
+
+impl OnlineRequirements {
+    /// Create the default requirements: any up, non-loopback interface with an address that is
+    /// not link-local.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also require a default route through the interface, for the family of its address.
+    pub fn default_route(mut self, required: bool) -> Self {
+        self.default_route = required;
+        self
+    }
+
+    /// Only consider the interface named `name`.
+    pub fn interface<S: Into<String>>(mut self, name: S) -> Self {
+        self.interface = Some(name.into());
+        self
+    }
+
+    fn is_usable(&self, iface: &Interface) -> bool {
+        let wanted = match self.interface {
+            Some(ref name) => *name == iface.name,
+            None => true,
+        };
+        wanted && iface.is_up() && !iface.is_loopback() && !crate::is_link_local(iface.ip())
+    }
+}
+
+/// Wait until the host is online as `requirements` describe, like
+/// `systemd-networkd-wait-online`, and return the interface address that satisfied them.
+///
+/// Failures to read the routing table are retried at the next poll, unless the platform does
+/// not support reading it at all.
+///
+/// Returns `None` if `timeout` elapses first. Without a timeout this waits indefinitely.
+pub fn wait_for_network_online(
+    requirements: &OnlineRequirements,
+    timeout: Option<Duration>,
+) -> io::Result<Option<Interface>> {
+    poll_until(timeout, |ifaces| {
+        let candidates: Vec<Interface> = ifaces
+            .into_iter()
+            .filter(|iface| requirements.is_usable(iface))
+            .collect();
+        if !requirements.default_route || candidates.is_empty() {
+            return Ok(candidates.into_iter().next());
+        }
+        let routes = match retry_on_failure(crate::routes::get_routes())? {
+            Some(routes) => routes,
+            None => return Ok(None),
+        };
+        // Interfaces without an index are matched to their link by name.
+        let names = if candidates.iter().any(|iface| iface.index.is_none()) {
+            match retry_on_failure(crate::get_if_names())? {
+                Some(names) => names,
+                None => return Ok(None),
+            }
+        } else {
+            Vec::new()
+        };
+        Ok(candidates
+            .into_iter()
+            .find(|iface| crate::routes::has_default_route_in(&routes, iface, &names)))
+    })
+}
+
+/// Turn a failure into `None` so that the caller polls again, unless the operation is not
+/// supported at all.
+fn retry_on_failure<T>(result: io::Result<T>) -> io::Result<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(err) if err.kind() == io::ErrorKind::Unsupported => Err(err),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Waits for the state of one named interface, e.g. the uplink a service depends on.
+#[derive(Debug, Clone)]
+pub struct InterfaceWatcher {
+    name: String,
+}
+
+impl InterfaceWatcher {
+    /// Watch the interface named `name`, which need not exist yet.
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        InterfaceWatcher { name: name.into() }
+    }
+
+    /// The name of the watched interface.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the current addresses of the interface, empty if it does not exist.
+    pub fn addresses(&self) -> io::Result<Vec<Interface>> {
+        let mut ifaces = crate::get_if_addrs()?;
+        ifaces.retain(|iface| iface.name == self.name);
+        Ok(ifaces)
+    }
+
+    /// Wait until the interface exists and is up, and return its first address. See
+    /// [`wait_for_interface`].
+    pub fn wait_up(&self, timeout: Option<Duration>) -> io::Result<Option<Interface>> {
+        wait_for_interface(&self.name, timeout)
+    }
+
+    /// Wait until the interface is gone or none of its addresses is up.
+    ///
+    /// Returns `false` if `timeout` elapses first. Without a timeout this waits indefinitely.
+    pub fn wait_down(&self, timeout: Option<Duration>) -> io::Result<bool> {
+        let down = poll_until(timeout, |ifaces| {
+            if ifaces
+                .iter()
+                .any(|iface| iface.name == self.name && iface.is_up())
+            {
+                Ok(None)
+            } else {
+                Ok(Some(()))
+            }
+        })?;
+        Ok(down.is_some())
+    }
+}
+
+/// Enumerate until some interface satisfies `predicate` or `timeout` elapses.
+fn poll<F>(timeout: Option<Duration>, mut predicate: F) -> io::Result<Option<Interface>>
+where
+    F: FnMut(&Interface) -> bool,
+{
+    poll_until(timeout, |ifaces| {
+        Ok(ifaces.into_iter().find(|iface| predicate(iface)))
+    })
+}
+
+/// Enumerate until `check` returns a value for the interfaces or `timeout` elapses.
+fn poll_until<T, F>(timeout: Option<Duration>, mut check: F) -> io::Result<Option<T>>
+where
+    F: FnMut(Vec<Interface>) -> io::Result<Option<T>>,
+{
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    loop {
+        if let Some(found) = check(crate::get_if_addrs()?)? {
+            return Ok(Some(found));
+        }
+        let interval = match deadline {
+            Some(deadline) => match deadline.checked_duration_since(Instant::now()) {
+                Some(remaining) if remaining > Duration::from_secs(0) => {
+                    remaining.min(POLL_INTERVAL)
+                }
+                _ => return Ok(None),
+            },
+            None => POLL_INTERVAL,
+        };
+        thread::sleep(interval);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OnlineRequirements;
+    use crate::routes::{has_default_route_in, Route};
+    use crate::{IfAddr, Ifv4Addr, Ifv6Addr, Interface};
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+    fn v4(name: &str, ip: Ipv4Addr, index: Option<u32>) -> Interface {
+        let addr = IfAddr::V4(Ifv4Addr::new(ip, Ipv4Addr::new(255, 255, 255, 0), None));
+        Interface::new(name.to_string(), addr, index)
+    }
+
+    fn v6(name: &str, ip: Ipv6Addr, index: Option<u32>) -> Interface {
+        let addr = IfAddr::V6(Ifv6Addr::new(ip, Ipv6Addr::from(!0u128 << 64), None));
+        Interface::new(name.to_string(), addr, index)
+    }
+
+    #[test]
+    fn test_is_usable() {
+        let any = OnlineRequirements::new();
+        let eth0 = v4("eth0", Ipv4Addr::new(192, 0, 2, 1), Some(2));
+        assert!(any.is_usable(&eth0));
+        assert!(!any.is_usable(&v4("lo", Ipv4Addr::LOCALHOST, Some(1))));
+        assert!(!any.is_usable(&v4("eth0", Ipv4Addr::new(169, 254, 0, 1), Some(2))));
+        assert!(!any.is_usable(&v6("eth0", "fe80::1".parse().unwrap(), Some(2))));
+        assert!(any.is_usable(&v6("eth0", "2001:db8::1".parse().unwrap(), Some(2))));
+
+        let mut down = eth0.clone();
+        down.flags = Some(0);
+        assert!(!any.is_usable(&down));
+
+        assert!(!OnlineRequirements::new()
+            .interface("wlan0")
+            .is_usable(&eth0));
+        assert!(OnlineRequirements::new().interface("eth0").is_usable(&eth0));
+    }
+
+    #[test]
+    fn test_has_default_route_in() {
+        let routes = [
+            Route {
+                destination: IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+                prefix_len: 0,
+                gateway: Some(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 254))),
+                index: 2,
+            },
+            Route {
+                destination: IpAddr::V4(Ipv4Addr::new(198, 51, 100, 0)),
+                prefix_len: 24,
+                gateway: None,
+                index: 3,
+            },
+        ];
+        let names = [("eth0".to_string(), 2), ("eth1".to_string(), 3)];
+
+        assert!(has_default_route_in(
+            &routes,
+            &v4("eth0", Ipv4Addr::new(192, 0, 2, 1), Some(2)),
+            &[]
+        ));
+        // Wrong family, and no default route through eth1.
+        assert!(!has_default_route_in(
+            &routes,
+            &v6("eth0", "2001:db8::1".parse().unwrap(), Some(2)),
+            &names
+        ));
+        assert!(!has_default_route_in(
+            &routes,
+            &v4("eth1", Ipv4Addr::new(198, 51, 100, 1), Some(3)),
+            &names
+        ));
+        // Without an index, e.g. with the `lean` feature, the name decides, labels included.
+        let unindexed = v4("eth0:1", Ipv4Addr::new(192, 0, 2, 2), None);
+        assert!(has_default_route_in(&routes, &unindexed, &names));
+        assert!(!has_default_route_in(&routes, &unindexed, &[]));
+    }
+}
@@ -0,0 +1,94 @@
+// Copyright 2018 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::options::Lookups;
+use crate::Interface;
+use std::fmt;
+use std::io;
+
+/// A problem with one entry the operating system reported, collected by
+/// [`get_if_addrs_with_warnings`].
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+#[non_exhaustive]
+pub struct IfAddrsWarning {
+    /// The name of the interface the entry belongs to, if it could be determined.
+    pub name: Option<String>,
+    /// The index of the interface the entry belongs to, if it could be determined.
+    pub index: Option<u32>,
+    /// What was wrong with the entry.
+    pub kind: IfAddrsWarningKind,
+}
+
+/// What was wrong with an entry, and what became of it.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[non_exhaustive]
+pub enum IfAddrsWarningKind {
+    /// The address could not be read. The entry was skipped.
+    MalformedAddress,
+    /// The entry came without a usable netmask. It is reported with an all-zero netmask.
+    MissingNetmask,
+    /// The index of the interface could not be determined. The entry is reported without one.
+    IndexLookupFailed,
+    /// The entry refers to an interface index the operating system did not list. The entry
+    /// was skipped.
+    UnknownInterface,
+}
+
+impl IfAddrsWarning {
+    pub(crate) fn new(name: Option<&str>, index: Option<u32>, kind: IfAddrsWarningKind) -> Self {
+        Self {
+            name: name.map(str::to_string),
+            index,
+            kind,
+        }
+    }
+}
+
+impl fmt::Display for IfAddrsWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let what = match self.kind {
+            IfAddrsWarningKind::MalformedAddress => "malformed address skipped",
+            IfAddrsWarningKind::MissingNetmask => "no usable netmask",
+            IfAddrsWarningKind::IndexLookupFailed => "interface index lookup failed",
+            IfAddrsWarningKind::UnknownInterface => "address on unknown interface skipped",
+        };
+        match (&self.name, self.index) {
+            (Some(name), _) => write!(f, "{}: {}", name, what),
+            (None, Some(index)) => write!(f, "interface {}: {}", index, what),
+            (None, None) => f.write_str(what),
+        }
+    }
+}
+
+/// Like [`get_if_addrs`](crate::get_if_addrs), but also return the problems found with
+/// individual entries, which are otherwise skipped or reported with missing details silently.
+///
+/// Unlike [`get_if_addrs`](crate::get_if_addrs), this always asks the operating system.
+pub fn get_if_addrs_with_warnings() -> io::Result<(Vec<Interface>, Vec<IfAddrsWarning>)> {
+    let mut warnings = Vec::new();
+    let ifaces = crate::os_get_if_addrs_with(Lookups::DEFAULT, &mut warnings)?;
+    Ok((ifaces, warnings))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{IfAddrsWarning, IfAddrsWarningKind};
+
+    #[test]
+    fn test_display() {
+        let warning =
+            IfAddrsWarning::new(Some("eth0"), Some(2), IfAddrsWarningKind::MissingNetmask);
+        assert_eq!(warning.to_string(), "eth0: no usable netmask");
+        let warning = IfAddrsWarning::new(None, Some(7), IfAddrsWarningKind::UnknownInterface);
+        assert_eq!(
+            warning.to_string(),
+            "interface 7: address on unknown interface skipped"
+        );
+    }
+}
@@ -7,221 +7,342 @@
 // specific language governing permissions and limitations relating to use of the SAFE Network
 // Software.
 
-use libc::{self, c_char, c_int, c_ulong, c_void, size_t};
+use libc::{c_char, c_int, c_ulong, c_void};
 use std::ffi::CStr;
-use std::{io, ptr};
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::{io, mem, ptr};
+use winapi::shared::ifdef::{MediaConnectStateConnected, MediaConnectStateDisconnected};
 use winapi::shared::minwindef::DWORD;
-use winapi::shared::winerror::ERROR_SUCCESS;
-use winapi::shared::ws2def::SOCKADDR;
+use winapi::shared::netioapi::{
+    FreeMibTable, GetIfEntry2, GetIpInterfaceEntry, GetUnicastIpAddressTable,
+    InitializeIpInterfaceEntry, MIB_IF_ROW2, MIB_IPINTERFACE_ROW, PMIB_UNICASTIPADDRESS_TABLE,
+};
+use winapi::shared::nldef::IpPrefixOriginDhcp;
+use winapi::shared::winerror::{ERROR_BUFFER_OVERFLOW, ERROR_NO_DATA, ERROR_SUCCESS, NO_ERROR};
+use winapi::shared::ws2def::{AF_INET, AF_INET6, AF_UNSPEC, SOCKADDR};
 
-#[repr(C)]
-pub struct SocketAddress {
-    pub lp_socket_address: *const SOCKADDR,
-    pub i_socket_address_length: c_int,
-}
-#[repr(C)]
-pub struct IpAdapterUnicastAddress {
-    pub length: c_ulong,
-    pub flags: DWORD,
-    pub next: *const IpAdapterUnicastAddress,
-    // Loads more follows, but I'm not bothering to map these for now
-    pub address: SocketAddress,
-}
-#[repr(C)]
-pub struct IpAdapterPrefix {
-    pub length: c_ulong,
-    pub flags: DWORD,
-    pub next: *const IpAdapterPrefix,
-    pub address: SocketAddress,
-    pub prefix_length: c_ulong,
-}
-#[repr(C)]
-pub struct IpAdapterAddresses {
-    pub length: c_ulong,
+use crate::luid::{self, Luid};
+use crate::{sockaddr, Cidr};
+
+/// An adapter reported by `GetAdaptersAddresses`, copied out of the result buffer so that
+/// nothing refers into it once the buffer is freed.
+pub struct Adapter {
+    pub name: String,
+    pub description: String,
     pub if_index: DWORD,
-    next: *const IpAdapterAddresses,
-    adapter_name: *const c_char,
-    first_unicast_address: *const IpAdapterUnicastAddress,
-    first_anycast_address: *const c_void,
-    first_multicast_address: *const c_void,
-    first_dns_server_address: *const c_void,
-    dns_suffix: *const c_void,
-    description: *const c_void,
-    friendly_name: *const c_void,
-    physical_address: [c_char; 8],
-    physical_address_length: DWORD,
+    pub ipv6_if_index: DWORD,
+    pub if_type: DWORD,
     flags: DWORD,
-    mtu: DWORD,
-    if_type: DWORD,
-    oper_status: c_int,
-    ipv6_if_index: DWORD,
-    zone_indices: [DWORD; 16],
-    // Loads more follows, but I'm not bothering to map these for now
-    first_prefix: *const IpAdapterPrefix,
+    pub physical_address: Option<[u8; 6]>,
+    /// The unicast addresses, without those `sockaddr::to_ipaddr` skips.
+    pub unicast_addresses: Vec<IpAddr>,
+    /// The on-link prefixes, without those `sockaddr::to_ipaddr` skips.
+    pub prefixes: Vec<Prefix>,
 }
 
-impl IpAdapterAddresses {
-    #[allow(unsafe_code)]
-    pub fn name(&self) -> String {
-        unsafe { CStr::from_ptr(self.adapter_name) }
-            .to_string_lossy()
-            .into_owned()
-    }
+pub struct Prefix {
+    pub address: IpAddr,
+    pub prefix_length: u32,
+}
 
-    pub fn prefixes(&self) -> PrefixesIterator {
-        PrefixesIterator {
-            _head: self,
-            next: self.first_prefix,
-        }
+impl Adapter {
+    /// Whether the adapter obtains its IPv4 configuration through DHCP.
+    pub fn dhcpv4_enabled(&self) -> bool {
+        const IP_ADAPTER_DHCP_ENABLED: DWORD = 0x4;
+        self.flags & IP_ADAPTER_DHCP_ENABLED != 0
     }
 
-    pub fn unicast_addresses(&self) -> UnicastAddressesIterator {
-        UnicastAddressesIterator {
-            _head: self,
-            next: self.first_unicast_address,
-        }
+    /// Whether IPv4 is enabled on the adapter, regardless of whether it has an address yet.
+    pub fn ipv4_enabled(&self) -> bool {
+        const IP_ADAPTER_IPV4_ENABLED: DWORD = 0x80;
+        self.flags & IP_ADAPTER_IPV4_ENABLED != 0
     }
-}
-
-#[link(name = "iphlpapi")]
-extern "system" {
-    /// Get adapter's addresses.
-    fn GetAdaptersAddresses(
-        family: c_ulong,
-        flags: c_ulong,
-        reserved: *const c_void,
-        addresses: *const IpAdapterAddresses,
-        size: *mut c_ulong,
-    ) -> c_ulong;
-}
 
-pub struct IfAddrs {
-    inner: *const IpAdapterAddresses,
-}
+    /// Whether IPv6 is enabled on the adapter, regardless of whether it has an address yet.
+    pub fn ipv6_enabled(&self) -> bool {
+        const IP_ADAPTER_IPV6_ENABLED: DWORD = 0x100;
+        self.flags & IP_ADAPTER_IPV6_ENABLED != 0
+    }
 
-impl IfAddrs {
+    /// Copy the details of `raw` and of the lists hanging off it.
     #[allow(unsafe_code)]
-    pub fn new() -> io::Result<Self> {
-        let mut buffersize: c_ulong = 15000;
-        let mut ifaddrs: *const IpAdapterAddresses;
-
-        loop {
-            unsafe {
-                ifaddrs = libc::malloc(buffersize as size_t) as *mut IpAdapterAddresses;
-                if ifaddrs.is_null() {
-                    panic!("Failed to allocate buffer in get_if_addrs()");
-                }
-
-                let retcode = GetAdaptersAddresses(
-                    0,
-                    // GAA_FLAG_SKIP_ANYCAST       |
-                    // GAA_FLAG_SKIP_MULTICAST     |
-                    // GAA_FLAG_SKIP_DNS_SERVER    |
-                    // GAA_FLAG_INCLUDE_PREFIX     |
-                    // GAA_FLAG_SKIP_FRIENDLY_NAME
-                    0x3e,
-                    ptr::null(),
-                    ifaddrs,
-                    &mut buffersize,
-                );
-
-                match retcode {
-                    ERROR_SUCCESS => break,
-                    111 => {
-                        libc::free(ifaddrs as *mut c_void);
-                        buffersize *= 2;
-                        continue;
-                    }
-                    _ => return Err(io::Error::last_os_error()),
-                }
+    unsafe fn copy_from(raw: &IpAdapterAddresses) -> Adapter {
+        let mut unicast_addresses = Vec::new();
+        let mut next = raw.first_unicast_address;
+        while let Some(unicast) = next.as_ref() {
+            unicast_addresses.extend(sockaddr::to_ipaddr(unicast.address.lp_socket_address));
+            next = unicast.next;
+        }
+
+        let mut prefixes = Vec::new();
+        let mut next = raw.first_prefix;
+        while let Some(prefix) = next.as_ref() {
+            if let Some(address) = sockaddr::to_ipaddr(prefix.address.lp_socket_address) {
+                prefixes.push(Prefix {
+                    address,
+                    prefix_length: prefix.prefix_length,
+                });
             }
+            next = prefix.next;
         }
 
-        Ok(Self { inner: ifaddrs })
-    }
+        let physical_address = if raw.physical_address_length == 6 {
+            let mut hw_addr = [0; 6];
+            for (dst, src) in hw_addr.iter_mut().zip(raw.physical_address.iter()) {
+                *dst = *src as u8;
+            }
+            Some(hw_addr)
+        } else {
+            None
+        };
 
-    pub fn iter(&self) -> IfAddrsIterator {
-        IfAddrsIterator {
-            _head: self,
-            next: self.inner,
+        Adapter {
+            name: CStr::from_ptr(raw.adapter_name)
+                .to_string_lossy()
+                .into_owned(),
+            description: wide_string(raw.description as *const u16),
+            if_index: raw.if_index,
+            ipv6_if_index: raw.ipv6_if_index,
+            if_type: raw.if_type,
+            flags: raw.flags,
+            physical_address,
+            unicast_addresses,
+            prefixes,
         }
     }
 }
 
-impl Drop for IfAddrs {
-    #[allow(unsafe_code)]
-    fn drop(&mut self) {
-        unsafe {
-            libc::free(self.inner as *mut c_void);
-        }
+/// Copy the NUL-terminated UTF-16 string at `wide`, which may be null.
+#[allow(unsafe_code)]
+unsafe fn wide_string(wide: *const u16) -> String {
+    if wide.is_null() {
+        return String::new();
     }
+    let mut len = 0;
+    while *wide.add(len) != 0 {
+        len += 1;
+    }
+    String::from_utf16_lossy(std::slice::from_raw_parts(wide, len))
 }
 
-pub struct IfAddrsIterator<'a> {
-    _head: &'a IfAddrs,
-    next: *const IpAdapterAddresses,
+/// List the adapters with their unicast addresses and prefixes.
+pub fn adapters() -> io::Result<Vec<Adapter>> {
+    // GAA_FLAG_SKIP_ANYCAST       |
+    // GAA_FLAG_SKIP_MULTICAST     |
+    // GAA_FLAG_SKIP_DNS_SERVER    |
+    // GAA_FLAG_INCLUDE_PREFIX     |
+    // GAA_FLAG_SKIP_FRIENDLY_NAME
+    get_adapters(0x3e)
 }
 
-impl<'a> Iterator for IfAddrsIterator<'a> {
-    type Item = &'a IpAdapterAddresses;
+/// List the adapters without any of their addresses.
+pub fn adapters_only() -> io::Result<Vec<Adapter>> {
+    // GAA_FLAG_SKIP_UNICAST       |
+    // GAA_FLAG_SKIP_ANYCAST       |
+    // GAA_FLAG_SKIP_MULTICAST     |
+    // GAA_FLAG_SKIP_DNS_SERVER    |
+    // GAA_FLAG_SKIP_FRIENDLY_NAME
+    get_adapters(0x2f)
+}
 
-    #[allow(unsafe_code)]
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.next.is_null() {
-            return None;
-        };
+/// The buffer size the last successful `GetAdaptersAddresses` call needed, so that the next one
+/// starts there instead of going through `ERROR_BUFFER_OVERFLOW` again.
+static LAST_BUFFER_SIZE: AtomicU32 = AtomicU32::new(15000);
 
-        Some(unsafe {
-            let result = &*self.next;
-            self.next = (*self.next).next;
+#[allow(unsafe_code)]
+fn get_adapters(flags: c_ulong) -> io::Result<Vec<Adapter>> {
+    let mut size: c_ulong = LAST_BUFFER_SIZE.load(Ordering::Relaxed);
+    loop {
+        // `u64` elements keep the buffer aligned for the structs written into it.
+        let mut buffer = vec![0u64; (size as usize + 7) / 8];
+        let ret = unsafe {
+            GetAdaptersAddresses(
+                0,
+                flags,
+                ptr::null(),
+                buffer.as_mut_ptr() as *mut IpAdapterAddresses,
+                &mut size,
+            )
+        };
+        match ret {
+            ERROR_SUCCESS => {
+                LAST_BUFFER_SIZE.store(size, Ordering::Relaxed);
+            }
+            // `size` now holds the required size.
+            ERROR_BUFFER_OVERFLOW => continue,
+            ERROR_NO_DATA => return Ok(Vec::new()),
+            err => return Err(io::Error::from_raw_os_error(err as i32)),
+        }
 
-            result
-        })
+        let mut adapters = Vec::new();
+        let mut next = buffer.as_ptr() as *const IpAdapterAddresses;
+        while let Some(raw) = unsafe { next.as_ref() } {
+            adapters.push(unsafe { Adapter::copy_from(raw) });
+            next = raw.next;
+        }
+        return Ok(adapters);
     }
 }
 
-pub struct PrefixesIterator<'a> {
-    _head: &'a IpAdapterAddresses,
-    next: *const IpAdapterPrefix,
-}
+/// Get the adapter with the given LUID from its `MIB_IF_ROW2` and the unicast address table,
+/// without going through the whole `GetAdaptersAddresses` list.
+///
+/// The prefixes are the on-link prefixes of the addresses, and DHCP counts as enabled when an
+/// IPv4 address was assigned through it.
+#[allow(unsafe_code)]
+pub fn adapter(luid: Luid) -> io::Result<Adapter> {
+    const IP_ADAPTER_DHCP_ENABLED: DWORD = 0x4;
+    const IP_ADAPTER_IPV4_ENABLED: DWORD = 0x80;
+    const IP_ADAPTER_IPV6_ENABLED: DWORD = 0x100;
 
-impl<'a> Iterator for PrefixesIterator<'a> {
-    type Item = &'a IpAdapterPrefix;
+    let mut row: MIB_IF_ROW2 = unsafe { mem::zeroed() };
+    row.InterfaceLuid.Value = luid.0;
+    let ret = unsafe { GetIfEntry2(&mut row) };
+    if ret != NO_ERROR {
+        return Err(io::Error::from_raw_os_error(ret as i32));
+    }
 
-    #[allow(unsafe_code)]
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.next.is_null() {
-            return None;
+    let mut flags = 0;
+    if ip_interface_enabled(luid, AF_INET) {
+        flags |= IP_ADAPTER_IPV4_ENABLED;
+    }
+    if ip_interface_enabled(luid, AF_INET6) {
+        flags |= IP_ADAPTER_IPV6_ENABLED;
+    }
+
+    let mut table: PMIB_UNICASTIPADDRESS_TABLE = ptr::null_mut();
+    let ret = unsafe { GetUnicastIpAddressTable(AF_UNSPEC as u16, &mut table) };
+    if ret != NO_ERROR {
+        return Err(io::Error::from_raw_os_error(ret as i32));
+    }
+    let rows = unsafe {
+        std::slice::from_raw_parts((*table).Table.as_ptr(), (*table).NumEntries as usize)
+    };
+    let mut unicast_addresses = Vec::new();
+    let mut prefixes = Vec::new();
+    for row in rows.iter().filter(|row| row.InterfaceLuid.Value == luid.0) {
+        let address = match sockaddr::to_ipaddr(&row.Address as *const _ as *const SOCKADDR) {
+            Some(address) => address,
+            None => continue,
         };
+        if address.is_ipv4() && row.PrefixOrigin == IpPrefixOriginDhcp {
+            flags |= IP_ADAPTER_DHCP_ENABLED;
+        }
+        unicast_addresses.push(address);
+        if let Some(cidr) = Cidr::new(address, row.OnLinkPrefixLength) {
+            prefixes.push(Prefix {
+                address: cidr.network().addr(),
+                prefix_length: u32::from(cidr.prefix_len()),
+            });
+        }
+    }
+    unsafe { FreeMibTable(table as *mut _) };
 
-        Some(unsafe {
-            let result = &*self.next;
-            self.next = (*self.next).next;
+    let physical_address = if row.PhysicalAddressLength == 6 {
+        let mut hw_addr = [0; 6];
+        hw_addr.copy_from_slice(&row.PhysicalAddress[..6]);
+        Some(hw_addr)
+    } else {
+        None
+    };
 
-            result
-        })
-    }
+    Ok(Adapter {
+        name: luid::format_guid(&row.InterfaceGuid),
+        description: unsafe { wide_string(row.Description.as_ptr()) },
+        if_index: row.InterfaceIndex,
+        ipv6_if_index: row.InterfaceIndex,
+        if_type: row.Type,
+        flags,
+        physical_address,
+        unicast_addresses,
+        prefixes,
+    })
 }
 
-pub struct UnicastAddressesIterator<'a> {
-    _head: &'a IpAdapterAddresses,
-    next: *const IpAdapterUnicastAddress,
+/// Whether the IPv4 or IPv6 stack, per `family`, is bound to the interface with the given LUID.
+#[allow(unsafe_code)]
+fn ip_interface_enabled(luid: Luid, family: c_int) -> bool {
+    let mut row: MIB_IPINTERFACE_ROW = unsafe { mem::zeroed() };
+    unsafe { InitializeIpInterfaceEntry(&mut row) };
+    row.Family = family as u16;
+    row.InterfaceLuid.Value = luid.0;
+    unsafe { GetIpInterfaceEntry(&mut row) == NO_ERROR }
 }
 
-impl<'a> Iterator for UnicastAddressesIterator<'a> {
-    type Item = &'a IpAdapterUnicastAddress;
-
-    #[allow(unsafe_code)]
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.next.is_null() {
-            return None;
-        };
+/// Whether the media of the interface `index` is connected, from its `MIB_IF_ROW2`.
+#[allow(unsafe_code)]
+pub fn media_connected(index: DWORD) -> Option<bool> {
+    let mut row: MIB_IF_ROW2 = unsafe { mem::zeroed() };
+    row.InterfaceIndex = index;
+    if unsafe { GetIfEntry2(&mut row) } != NO_ERROR {
+        return None;
+    }
+    match row.MediaConnectState {
+        MediaConnectStateConnected => Some(true),
+        MediaConnectStateDisconnected => Some(false),
+        _ => None,
+    }
+}
 
-        Some(unsafe {
-            let result = &*self.next;
-            self.next = (*self.next).next;
+#[repr(C)]
+#[allow(dead_code)]
+struct SocketAddress {
+    lp_socket_address: *const SOCKADDR,
+    i_socket_address_length: c_int,
+}
+#[repr(C)]
+#[allow(dead_code)]
+struct IpAdapterUnicastAddress {
+    length: c_ulong,
+    flags: DWORD,
+    next: *const IpAdapterUnicastAddress,
+    // Loads more follows, but I'm not bothering to map these for now
+    address: SocketAddress,
+}
+#[repr(C)]
+#[allow(dead_code)]
+struct IpAdapterPrefix {
+    length: c_ulong,
+    flags: DWORD,
+    next: *const IpAdapterPrefix,
+    address: SocketAddress,
+    prefix_length: c_ulong,
+}
+#[repr(C)]
+#[allow(dead_code)]
+struct IpAdapterAddresses {
+    length: c_ulong,
+    if_index: DWORD,
+    next: *const IpAdapterAddresses,
+    adapter_name: *const c_char,
+    first_unicast_address: *const IpAdapterUnicastAddress,
+    first_anycast_address: *const c_void,
+    first_multicast_address: *const c_void,
+    first_dns_server_address: *const c_void,
+    dns_suffix: *const c_void,
+    description: *const c_void,
+    friendly_name: *const c_void,
+    physical_address: [c_char; 8],
+    physical_address_length: DWORD,
+    flags: DWORD,
+    mtu: DWORD,
+    if_type: DWORD,
+    oper_status: c_int,
+    ipv6_if_index: DWORD,
+    zone_indices: [DWORD; 16],
+    // Loads more follows, but I'm not bothering to map these for now
+    first_prefix: *const IpAdapterPrefix,
+}
 
-            result
-        })
-    }
+#[link(name = "iphlpapi")]
+extern "system" {
+    /// Get adapter's addresses.
+    fn GetAdaptersAddresses(
+        family: c_ulong,
+        flags: c_ulong,
+        reserved: *const c_void,
+        addresses: *mut IpAdapterAddresses,
+        size: *mut c_ulong,
+    ) -> c_ulong;
 }
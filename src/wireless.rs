@@ -0,0 +1,328 @@
+// Copyright 2018 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Signal strength of wireless links, from nl80211 on Linux and the WLAN API on Windows.
+
+use crate::Interface;
+use std::io;
+
+/// The signal strength of a wireless link.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct SignalStrength {
+    /// The received signal strength in dBm, if reported.
+    pub rssi: Option<i32>,
+    /// The link quality in percent, where `0` corresponds to -100 dBm or worse and `100` to
+    /// -50 dBm or better.
+    pub quality: u8,
+}
+
+impl SignalStrength {
+    fn from_rssi(rssi: i32) -> Self {
+        SignalStrength {
+            rssi: Some(rssi),
+            quality: (2 * (rssi + 100)).clamp(0, 100) as u8,
+        }
+    }
+}
+
+/// Get the signal strength of `iface`.
+///
+/// Returns `None` if the interface is not wireless or not associated with a network.
+pub fn signal_strength(iface: &Interface) -> io::Result<Option<SignalStrength>> {
+    os::signal_strength(iface)
+}
+
+#[cfg(target_os = "linux")]
+mod os {
+    use super::SignalStrength;
+    use crate::nlmsg::{self, Socket, NLM_F_DUMP};
+    use crate::Interface;
+    use std::ffi::CString;
+    use std::io;
+
+    const GENL_HDRLEN: usize = 4;
+
+    const GENL_ID_CTRL: u16 = 0x10;
+    const CTRL_CMD_GETFAMILY: u8 = 3;
+    const CTRL_ATTR_FAMILY_ID: u16 = 1;
+    const CTRL_ATTR_FAMILY_NAME: u16 = 2;
+
+    const NL80211_CMD_GET_STATION: u8 = 17;
+    const NL80211_ATTR_IFINDEX: u16 = 3;
+    const NL80211_ATTR_STA_INFO: u16 = 21;
+    const NL80211_STA_INFO_SIGNAL: u16 = 7;
+
+    pub fn signal_strength(iface: &Interface) -> io::Result<Option<SignalStrength>> {
+        let index = match iface.index {
+            Some(index) => index,
+            None => match if_nametoindex(&iface.name) {
+                Some(index) => index,
+                None => return Ok(None),
+            },
+        };
+
+        match station_signal(index) {
+            Ok(rssi) => Ok(rssi.map(SignalStrength::from_rssi)),
+            // Not a wireless interface, or no nl80211 support in the kernel.
+            Err(ref err)
+                if [libc::ENODEV, libc::EOPNOTSUPP, libc::EINVAL, libc::ENOENT]
+                    .iter()
+                    .any(|&errno| err.raw_os_error() == Some(errno)) =>
+            {
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Dump the stations of the interface `index` and return the first signal reported. For a
+    /// client interface the only station is the access point it is associated with.
+    fn station_signal(index: u32) -> io::Result<Option<i32>> {
+        let socket = Socket::open(libc::NETLINK_GENERIC)?;
+
+        let family = request(
+            &socket,
+            GENL_ID_CTRL,
+            0,
+            CTRL_CMD_GETFAMILY,
+            1,
+            CTRL_ATTR_FAMILY_NAME,
+            b"nl80211\0",
+        )?
+        .iter()
+        .flat_map(|payload| nlmsg::attrs(payload.get(GENL_HDRLEN..).unwrap_or(&[])))
+        .find(|&(attr_type, value)| attr_type == CTRL_ATTR_FAMILY_ID && value.len() >= 2)
+        .map(|(_, value)| u16::from_ne_bytes([value[0], value[1]]));
+        let family = match family {
+            Some(family) => family,
+            None => return Ok(None),
+        };
+
+        let stations = request(
+            &socket,
+            family,
+            NLM_F_DUMP,
+            NL80211_CMD_GET_STATION,
+            0,
+            NL80211_ATTR_IFINDEX,
+            &index.to_ne_bytes(),
+        )?;
+        Ok(first_signal(&stations))
+    }
+
+    /// Find the first signal in the `NL80211_CMD_GET_STATION` reply payloads `stations`.
+    /// Truncated attributes end the parsing of the message they are in.
+    fn first_signal(stations: &[Vec<u8>]) -> Option<i32> {
+        for payload in stations {
+            for (attr_type, value) in nlmsg::attrs(payload.get(GENL_HDRLEN..).unwrap_or(&[])) {
+                if attr_type != NL80211_ATTR_STA_INFO {
+                    continue;
+                }
+                for (info_type, info) in nlmsg::attrs(value) {
+                    if info_type == NL80211_STA_INFO_SIGNAL && !info.is_empty() {
+                        return Some(i32::from(info[0] as i8));
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Send a generic netlink request with a single attribute and collect the payloads of the
+    /// replies.
+    fn request(
+        socket: &Socket,
+        msg_type: u16,
+        flags: u16,
+        cmd: u8,
+        version: u8,
+        attr_type: u16,
+        attr: &[u8],
+    ) -> io::Result<Vec<Vec<u8>>> {
+        let mut body = vec![cmd, version, 0, 0];
+        nlmsg::push_attr(&mut body, attr_type, attr);
+        let replies = socket.transact(&nlmsg::request(msg_type, flags, 1, &body))?;
+        Ok(replies.into_iter().map(|(_, payload)| payload).collect())
+    }
+
+    #[allow(unsafe_code)]
+    fn if_nametoindex(name: &str) -> Option<u32> {
+        let name = CString::new(name).ok()?;
+        match unsafe { libc::if_nametoindex(name.as_ptr()) } {
+            0 => None,
+            index => Some(index),
+        }
+    }
+
+    #[cfg(all(test, target_endian = "little"))]
+    mod tests {
+        use super::first_signal;
+
+        // A station reply captured on a little-endian host: the generic netlink header, the
+        // interface index, MAC address and generation, then the nested station info with the
+        // inactive time, received and sent bytes, signal and average signal.
+        const STATION: &[u8] = &[
+            0x13, 0x01, 0x00, 0x00, //
+            0x08, 0x00, 0x03, 0x00, 0x03, 0x00, 0x00, 0x00, //
+            0x0a, 0x00, 0x06, 0x00, 0x52, 0x54, 0x00, 0x12, 0x34, 0x56, 0x00, 0x00, //
+            0x08, 0x00, 0x2e, 0x00, 0x2a, 0x00, 0x00, 0x00, //
+            0x2c, 0x00, 0x15, 0x80, //
+            0x08, 0x00, 0x01, 0x00, 0xc8, 0x00, 0x00, 0x00, //
+            0x08, 0x00, 0x02, 0x00, 0x10, 0x27, 0x00, 0x00, //
+            0x08, 0x00, 0x03, 0x00, 0xe8, 0x03, 0x00, 0x00, //
+            0x05, 0x00, 0x07, 0x00, 0xc4, 0x00, 0x00, 0x00, //
+            0x05, 0x00, 0x0d, 0x00, 0xc6, 0x00, 0x00, 0x00, //
+        ];
+
+        #[test]
+        fn test_first_signal() {
+            assert_eq!(first_signal(&[STATION.to_vec()]), Some(-60));
+            assert_eq!(first_signal(&[]), None);
+
+            // The station info is cut off before the signal, and the signal claims more bytes
+            // than the station info holds. Neither hides the signal of a later station.
+            let truncated = STATION[..48].to_vec();
+            let mut overlong = STATION.to_vec();
+            overlong[60] = 0x20;
+            let stations = [truncated, overlong, STATION.to_vec()];
+            assert_eq!(first_signal(&stations[..1]), None);
+            assert_eq!(first_signal(&stations[1..2]), None);
+            assert_eq!(first_signal(&stations), Some(-60));
+        }
+    }
+}
+
+#[cfg(windows)]
+mod os {
+    use super::SignalStrength;
+    use crate::Interface;
+    use std::{io, mem, ptr, slice};
+    use winapi::shared::guiddef::GUID;
+    use winapi::shared::winerror::ERROR_SUCCESS;
+    use winapi::um::winnt::HANDLE;
+    use winapi::um::wlanapi::{
+        wlan_intf_opcode_current_connection, wlan_intf_opcode_rssi, WlanCloseHandle,
+        WlanEnumInterfaces, WlanFreeMemory, WlanOpenHandle, WlanQueryInterface,
+        PWLAN_INTERFACE_INFO_LIST, WLAN_CONNECTION_ATTRIBUTES, WLAN_INTERFACE_INFO,
+    };
+
+    struct Handle(HANDLE);
+
+    impl Drop for Handle {
+        #[allow(unsafe_code)]
+        fn drop(&mut self) {
+            unsafe { WlanCloseHandle(self.0, ptr::null_mut()) };
+        }
+    }
+
+    #[allow(unsafe_code)]
+    pub fn signal_strength(iface: &Interface) -> io::Result<Option<SignalStrength>> {
+        let mut version = 0;
+        let mut handle = ptr::null_mut();
+        let ret = unsafe { WlanOpenHandle(2, ptr::null_mut(), &mut version, &mut handle) };
+        if ret != ERROR_SUCCESS {
+            return Err(io::Error::from_raw_os_error(ret as i32));
+        }
+        let handle = Handle(handle);
+
+        let mut list: PWLAN_INTERFACE_INFO_LIST = ptr::null_mut();
+        let ret = unsafe { WlanEnumInterfaces(handle.0, ptr::null_mut(), &mut list) };
+        if ret != ERROR_SUCCESS {
+            return Err(io::Error::from_raw_os_error(ret as i32));
+        }
+        let guid = unsafe {
+            let infos: &[WLAN_INTERFACE_INFO] = slice::from_raw_parts(
+                (*list).InterfaceInfo.as_ptr(),
+                (*list).dwNumberOfItems as usize,
+            );
+            let guid = infos
+                .iter()
+                .map(|info| info.InterfaceGuid)
+                .find(|guid| format_guid(guid).eq_ignore_ascii_case(&iface.name));
+            WlanFreeMemory(list as *mut _);
+            guid
+        };
+        let guid = match guid {
+            Some(guid) => guid,
+            None => return Ok(None),
+        };
+
+        let quality = match query::<WLAN_CONNECTION_ATTRIBUTES>(
+            &handle,
+            &guid,
+            wlan_intf_opcode_current_connection,
+        ) {
+            Some(attrs) => attrs.wlanAssociationAttributes.wlanSignalQuality.min(100) as u8,
+            // Not connected.
+            None => return Ok(None),
+        };
+        let rssi = query::<i32>(&handle, &guid, wlan_intf_opcode_rssi);
+        Ok(Some(SignalStrength { rssi, quality }))
+    }
+
+    /// Query a fixed-size value of the interface `guid`.
+    #[allow(unsafe_code)]
+    fn query<T: Copy>(handle: &Handle, guid: &GUID, opcode: u32) -> Option<T> {
+        let mut size = 0;
+        let mut data = ptr::null_mut();
+        let ret = unsafe {
+            WlanQueryInterface(
+                handle.0,
+                guid,
+                opcode,
+                ptr::null_mut(),
+                &mut size,
+                &mut data,
+                ptr::null_mut(),
+            )
+        };
+        if ret != ERROR_SUCCESS || data.is_null() {
+            return None;
+        }
+        let value = if size as usize >= mem::size_of::<T>() {
+            Some(unsafe { ptr::read_unaligned(data as *const T) })
+        } else {
+            None
+        };
+        unsafe { WlanFreeMemory(data) };
+        value
+    }
+
+    /// Format `guid` like the adapter names GetAdaptersAddresses reports.
+    fn format_guid(guid: &GUID) -> String {
+        format!(
+            "{{{:08X}-{:04X}-{:04X}-{:02X}{:02X}-{:02X}{:02X}{:02X}{:02X}{:02X}{:02X}}}",
+            guid.Data1,
+            guid.Data2,
+            guid.Data3,
+            guid.Data4[0],
+            guid.Data4[1],
+            guid.Data4[2],
+            guid.Data4[3],
+            guid.Data4[4],
+            guid.Data4[5],
+            guid.Data4[6],
+            guid.Data4[7]
+        )
+    }
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+mod os {
+    use super::SignalStrength;
+    use crate::Interface;
+    use std::io;
+
+    pub fn signal_strength(_iface: &Interface) -> io::Result<Option<SignalStrength>> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "wireless signal strength is not supported on this platform",
+        ))
+    }
+}
@@ -0,0 +1,92 @@
+// Copyright 2018 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Scoped IPv6 addresses, whose zone (the interface index, as in `sin6_scope_id`) is part of
+//! their identity: `fe80::1` on one link is a different host than `fe80::1` on another.
+
+use std::net::Ipv6Addr;
+
+/// Check whether `ip` is only meaningful together with a zone: link-local unicast
+/// (`fe80::/10`) and interface- or link-local multicast addresses.
+pub fn needs_zone(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    first & 0xffc0 == 0xfe80 || (first & 0xff00 == 0xff00 && matches!(first & 0x000f, 1 | 2))
+}
+
+/// Compare two IPv6 addresses with their zones, a zone of `0` meaning none.
+///
+/// Zones only matter for addresses that [need one](needs_zone); other addresses are equal
+/// regardless of the zone they came with.
+pub fn scoped_eq(a: Ipv6Addr, zone_a: u32, b: Ipv6Addr, zone_b: u32) -> bool {
+    a == b && (!needs_zone(a) || zone_a == zone_b)
+}
+
+/// Format `ip` with its zone appended when it [needs one](needs_zone), e.g. `fe80::1%eth0`.
+///
+/// Unix platforms name the zone after the interface, falling back to its index if the
+/// interface is gone; Windows uses the index, e.g. `fe80::1%3`.
+pub fn format_scoped(ip: Ipv6Addr, zone: u32) -> String {
+    if zone == 0 || !needs_zone(ip) {
+        return ip.to_string();
+    }
+    #[cfg(unix)]
+    {
+        if let Some(name) = if_indextoname(zone) {
+            return format!("{}%{}", ip, name);
+        }
+    }
+    format!("{}%{}", ip, zone)
+}
+
+#[cfg(unix)]
+#[allow(unsafe_code)]
+fn if_indextoname(index: u32) -> Option<String> {
+    let mut buf = [0 as libc::c_char; libc::IF_NAMESIZE];
+    let name = unsafe { libc::if_indextoname(index, buf.as_mut_ptr()) };
+    if name.is_null() {
+        return None;
+    }
+    Some(
+        unsafe { std::ffi::CStr::from_ptr(name) }
+            .to_string_lossy()
+            .into_owned(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_scoped, needs_zone, scoped_eq};
+    use std::net::Ipv6Addr;
+
+    #[test]
+    fn test_scoped_eq() {
+        let link_local: Ipv6Addr = "fe80::1".parse().unwrap();
+        let global: Ipv6Addr = "2001:db8::1".parse().unwrap();
+        let all_nodes: Ipv6Addr = "ff02::1".parse().unwrap();
+
+        assert!(needs_zone(link_local));
+        assert!(needs_zone(all_nodes));
+        assert!(!needs_zone(global));
+        assert!(!needs_zone("ff05::1".parse().unwrap()));
+
+        assert!(scoped_eq(link_local, 2, link_local, 2));
+        assert!(!scoped_eq(link_local, 2, link_local, 3));
+        assert!(!scoped_eq(all_nodes, 0, all_nodes, 3));
+        assert!(scoped_eq(global, 2, global, 3));
+        assert!(!scoped_eq(global, 2, link_local, 2));
+
+        assert_eq!(format_scoped(global, 2), "2001:db8::1");
+        assert_eq!(format_scoped(link_local, 0), "fe80::1");
+        // No interface has the largest index.
+        assert_eq!(
+            format_scoped(link_local, u32::MAX),
+            format!("fe80::1%{}", u32::MAX)
+        );
+    }
+}
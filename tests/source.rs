@@ -0,0 +1,259 @@
+// Copyright 2018 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// http://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Tests against a registered `IfAddrsSource`. The source is global, so they live in their own
+//! test binary and take turns through `LOCK`.
+
+#![cfg(feature = "std")]
+
+use if_addrs::{
+    clear_source, get_if_addrs, get_if_addrs_into, get_if_addrs_timeout, get_if_addrs_with_options,
+    set_source, wait_for_interface, IfAddr, IfAddrsOptions, IfAddrsSource, Ifv4Addr, Ifv6Addr,
+    Interface, InterfaceWatcher, OsSource,
+};
+use std::io;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::Duration;
+
+static LOCK: Mutex<()> = Mutex::new(());
+
+// IFF_UP | IFF_RUNNING
+const UP: u32 = 0x41;
+
+struct Fixed {
+    ifaces: Vec<Interface>,
+    delay: Duration,
+    fail: bool,
+}
+
+impl IfAddrsSource for Fixed {
+    fn get_if_addrs(&self) -> io::Result<Vec<Interface>> {
+        thread::sleep(self.delay);
+        if self.fail {
+            return Err(io::Error::other("source failed"));
+        }
+        Ok(self.ifaces.clone())
+    }
+}
+
+/// Register a source for the duration of a test.
+struct Registered {
+    _guard: MutexGuard<'static, ()>,
+}
+
+impl Registered {
+    fn new(ifaces: Vec<Interface>, delay: Duration, fail: bool) -> Self {
+        let guard = LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+        set_source(Fixed {
+            ifaces,
+            delay,
+            fail,
+        });
+        Registered { _guard: guard }
+    }
+}
+
+impl Drop for Registered {
+    fn drop(&mut self) {
+        clear_source();
+    }
+}
+
+fn v4(name: &str, ip: Ipv4Addr, index: Option<u32>) -> Interface {
+    let addr = IfAddr::V4(Ifv4Addr::new(ip, Ipv4Addr::new(255, 255, 0, 0), None));
+    let mut iface = Interface::new(name.to_string(), addr, index);
+    iface.flags = Some(UP);
+    iface.has_carrier = Some(true);
+    iface
+}
+
+fn v6(name: &str, ip: &str, index: Option<u32>) -> Interface {
+    let addr = IfAddr::V6(Ifv6Addr::new(
+        ip.parse().unwrap(),
+        Ipv6Addr::from(!0u128 << 64),
+        None,
+    ));
+    let mut iface = Interface::new(name.to_string(), addr, index);
+    iface.flags = Some(UP);
+    iface
+}
+
+#[test]
+fn test_exclude_link_local() {
+    let eth0 = v4("eth0", Ipv4Addr::new(192, 0, 2, 1), Some(2));
+    let eth0_v6 = v6("eth0", "2001:db8::1", Some(2));
+    let apipa = v4("eth1", Ipv4Addr::new(169, 254, 1, 1), Some(3));
+    let link_local = v6("eth0", "fe80::1", Some(2));
+    let all = vec![
+        eth0.clone(),
+        apipa.clone(),
+        link_local.clone(),
+        eth0_v6.clone(),
+    ];
+    let _source = Registered::new(all.clone(), Duration::from_secs(0), false);
+
+    assert_eq!(
+        get_if_addrs_with_options(&IfAddrsOptions::new()).unwrap(),
+        all
+    );
+    let options = IfAddrsOptions::new().exclude_link_local(true);
+    assert_eq!(
+        get_if_addrs_with_options(&options).unwrap(),
+        vec![eth0, eth0_v6]
+    );
+}
+
+#[test]
+fn test_set_source() {
+    let eth0 = v4("eth0", Ipv4Addr::new(192, 0, 2, 1), Some(2));
+    let source = Registered::new(vec![eth0.clone()], Duration::from_secs(0), false);
+    assert_eq!(get_if_addrs().unwrap(), vec![eth0.clone()]);
+    // The operating system stays reachable through `OsSource`.
+    assert!(OsSource
+        .get_if_addrs()
+        .unwrap()
+        .iter()
+        .any(|iface| iface.is_loopback()));
+
+    drop(source);
+    let _source = Registered::new(Vec::new(), Duration::from_secs(0), true);
+    assert!(get_if_addrs().is_err());
+}
+
+#[test]
+fn test_clear_source() {
+    let eth0 = v4("eth0", Ipv4Addr::new(192, 0, 2, 1), Some(2));
+    drop(Registered::new(
+        vec![eth0.clone()],
+        Duration::from_secs(0),
+        false,
+    ));
+    let _lock = LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+    let ifaces = get_if_addrs().unwrap();
+    assert!(!ifaces.contains(&eth0));
+    assert!(ifaces.iter().any(|iface| iface.is_loopback()));
+}
+
+#[test]
+fn test_sorted() {
+    let eth0 = v4("eth0", Ipv4Addr::new(192, 0, 2, 1), Some(2));
+    let eth0_v6 = v6("eth0", "2001:db8::1", Some(2));
+    let eth0_1 = v4("eth0:1", Ipv4Addr::new(192, 0, 2, 2), Some(2));
+    let lo = v4("lo", Ipv4Addr::LOCALHOST, Some(1));
+    let tun0 = v4("tun0", Ipv4Addr::new(10, 8, 0, 2), None);
+    let tap0 = v4("tap0", Ipv4Addr::new(10, 9, 0, 2), None);
+    let _source = Registered::new(
+        vec![
+            tun0.clone(),
+            eth0_v6.clone(),
+            eth0_1.clone(),
+            tap0.clone(),
+            eth0.clone(),
+            lo.clone(),
+        ],
+        Duration::from_secs(0),
+        false,
+    );
+
+    // By index, then name, then IPv4 before IPv6. Interfaces without an index come last.
+    let sorted = get_if_addrs_with_options(&IfAddrsOptions::new().sorted(true)).unwrap();
+    assert_eq!(sorted, vec![lo, eth0, eth0_v6, eth0_1, tap0, tun0]);
+}
+
+#[test]
+fn test_wait_for_interface() {
+    let eth0 = v4("eth0", Ipv4Addr::new(192, 0, 2, 1), Some(2));
+    let mut eth1 = v4("eth1", Ipv4Addr::new(198, 51, 100, 1), Some(3));
+    // Administratively up, but not running.
+    eth1.flags = Some(0x1);
+    let _source = Registered::new(vec![eth0.clone(), eth1], Duration::from_secs(0), false);
+    let now = Some(Duration::from_secs(0));
+
+    assert_eq!(wait_for_interface("eth0", now).unwrap(), Some(eth0));
+    assert_eq!(wait_for_interface("eth1", now).unwrap(), None);
+    assert_eq!(
+        wait_for_interface("wlan0", Some(Duration::from_millis(300))).unwrap(),
+        None
+    );
+}
+
+#[test]
+fn test_interface_watcher() {
+    let eth0 = v4("eth0", Ipv4Addr::new(192, 0, 2, 1), Some(2));
+    let mut eth1 = v4("eth1", Ipv4Addr::new(198, 51, 100, 1), Some(3));
+    eth1.flags = Some(0x1);
+    let _source = Registered::new(
+        vec![eth0.clone(), eth1.clone()],
+        Duration::from_secs(0),
+        false,
+    );
+    let now = Some(Duration::from_secs(0));
+
+    let watcher = InterfaceWatcher::new("eth0");
+    assert_eq!(watcher.name(), "eth0");
+    assert_eq!(watcher.addresses().unwrap(), vec![eth0.clone()]);
+    assert_eq!(watcher.wait_up(now).unwrap(), Some(eth0));
+    assert!(!watcher.wait_down(now).unwrap());
+
+    // Present but not running.
+    let watcher = InterfaceWatcher::new("eth1");
+    assert_eq!(watcher.addresses().unwrap(), vec![eth1]);
+    assert_eq!(watcher.wait_up(now).unwrap(), None);
+    assert!(watcher.wait_down(now).unwrap());
+
+    let watcher = InterfaceWatcher::new("wlan0");
+    assert!(watcher.addresses().unwrap().is_empty());
+    assert!(watcher.wait_down(now).unwrap());
+}
+
+#[test]
+fn test_get_if_addrs_into() {
+    let eth0 = v4("eth0", Ipv4Addr::new(192, 0, 2, 1), Some(2));
+    let source = Registered::new(vec![eth0.clone()], Duration::from_secs(0), false);
+    let mut ifaces = Vec::with_capacity(16);
+    ifaces.push(v4("stale0", Ipv4Addr::new(198, 51, 100, 1), Some(9)));
+    let buf = ifaces.as_ptr();
+
+    get_if_addrs_into(&mut ifaces).unwrap();
+    assert_eq!(ifaces, vec![eth0]);
+    assert_eq!(ifaces.as_ptr(), buf);
+
+    drop(source);
+    let _source = Registered::new(Vec::new(), Duration::from_secs(0), true);
+    assert!(get_if_addrs_into(&mut ifaces).is_err());
+    assert!(ifaces.is_empty());
+}
+
+#[test]
+fn test_get_if_addrs_timeout() {
+    let eth0 = v4("eth0", Ipv4Addr::new(192, 0, 2, 1), Some(2));
+    let _source = Registered::new(vec![eth0.clone()], Duration::from_millis(200), false);
+
+    let err = get_if_addrs_timeout(Duration::from_millis(10)).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    assert_eq!(
+        get_if_addrs_timeout(Duration::from_secs(10)).unwrap(),
+        vec![eth0]
+    );
+}
+
+#[test]
+fn test_skip_lookups() {
+    let eth0 = v4("eth0", Ipv4Addr::new(192, 0, 2, 1), Some(2));
+    let _source = Registered::new(vec![eth0.clone()], Duration::from_secs(0), false);
+
+    let ifaces = get_if_addrs_with_options(&IfAddrsOptions::new().skip_index(true)).unwrap();
+    assert_eq!((ifaces[0].index, ifaces[0].has_carrier), (None, Some(true)));
+    let ifaces = get_if_addrs_with_options(&IfAddrsOptions::new().skip_status(true)).unwrap();
+    assert_eq!((ifaces[0].index, ifaces[0].has_carrier), (Some(2), None));
+    let ifaces = get_if_addrs_with_options(&IfAddrsOptions::new()).unwrap();
+    assert_eq!(ifaces, vec![eth0]);
+}